@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub const USAGE: &str = "Usage:
+    advent-of-code run --day N [--part P] [--input FILE] [--explain] [--words FILE] [--bag CUBES] [--bag-file FILE]
+        [--rules FILE] [--offset T] [--render [--color] [--start R,C,DIR] [--energized]] [--scale-factors LIST]
+        [--between A,B] [--trace] [--presses N] [--export-obj FILE [--color]] [--query]
+        [--heatmap [--steps N] [--start R,C] [--ppm FILE]] [--encounters] [--groups K] [--export-dot FILE] [--export-graphml FILE]
+    advent-of-code submit --day N --part P [--input FILE] [--endpoint URL] [--history FILE]
+    advent-of-code new --day N
+
+The input file defaults to input.txt, and an input of - reads standard input. When submitting, the session cookie is read from the
+ADVENT_SESSION environment variable, and the endpoint defaults to ADVENT_SUBMIT_URL if set.
+Any `{day}` in the endpoint is replaced by the day number.
+--explain prints how the answer was reached, for the days that support it (days 1 and 5).
+--words solves part 2 again, spelling the digits with the word list in FILE (day 1).
+--bag checks which games are possible with a bag such as `12 red, 13 green`, and the smallest bag
+for each game, with --bag-file reading the bag from FILE instead (day 2).
+--rules solves the puzzle again using the custom rules in FILE (day 7).
+--offset predicts the value of every history at offset T from its first value (day 9).
+--render draws the map, with --color adding ANSI colors (day 10), or draws the beam starting at
+--start, with --energized showing the energized tiles instead of the beam (day 16), or draws the
+settled bricks from the side, with --color coloring them by chain reaction size (day 22).
+--scale-factors sums the galaxy distances for each comma separated scale factor, and --between
+prints the distance between two galaxies, numbered from 1 (day 11).
+--trace prints the state of the boxes after every step (day 15).
+--presses counts the pulses sent over N button presses, up to the u64 limit if the network repeats
+a state within a million presses, and up to a million otherwise (day 20).
+--export-obj writes the settled bricks to FILE as a Wavefront OBJ model, with --color adding
+vertex colors by chain reaction size (day 22).
+--query reads sets of brick numbers from standard input and prints which bricks fall when each
+set is disintegrated at once (day 22).
+--heatmap draws how many steps it takes to reach every garden plot from S, or from --start,
+with --steps marking the plots reachable in exactly N steps and --ppm writing a PPM image to FILE instead (day 21).
+--encounters reports which hailstones collide in 3D, which have parallel or collinear paths, and
+the closest approach of the others (day 24).
+--groups splits the wire map into K groups using minimum cuts, reporting the wires cut and the
+size of each group, and --export-dot and --export-graphml write the wire map to FILE, coloring
+the groups and dashing the cut wires (day 25).";
+
+///The flags passed after a subcommand, for example `--day 5`.
+/// A flag that is not followed by a value (such as `--color`) is stored
+/// with a value of None.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Flags {
+    flags: HashMap<String, Option<String>>,
+}
+
+impl Flags {
+    fn parse(args: &[String]) -> Result<Flags, String> {
+        //! Pair up each `--flag` with the argument following it, unless that
+        //! argument is itself a flag.
+        let mut flags = HashMap::new();
+        let mut args_iterator = args.iter().peekable();
+        while let Some(arg) = args_iterator.next() {
+            let flag_name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument `{}`", arg))?;
+            let flag_value = match args_iterator.peek() {
+                Some(next_arg) if !next_arg.starts_with("--") => args_iterator.next().cloned(),
+                _ => None,
+            };
+            flags.insert(flag_name.to_owned(), flag_value);
+        }
+        Ok(Flags { flags })
+    }
+
+    pub fn is_set(&self, flag_name: &str) -> bool {
+        //! Whether the flag was passed at all, with or without a value.
+        self.flags.contains_key(flag_name)
+    }
+
+    pub fn value(&self, flag_name: &str) -> Option<&str> {
+        //! The value passed for the flag, if any.
+        self.flags.get(flag_name)?.as_deref()
+    }
+
+    pub fn parse_value<T: FromStr>(&self, flag_name: &str) -> Result<Option<T>, String> {
+        //! Parse the value of the flag into a `T`, returning None if the flag was not passed.
+        match self.value(flag_name) {
+            Some(value) => value
+                .parse::<T>()
+                .map(Some)
+                .map_err(|_| format!("Invalid value `{}` for --{}", value, flag_name)),
+            None if self.is_set(flag_name) => Err(format!("--{} requires a value", flag_name)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn parse_list<T: FromStr>(&self, flag_name: &str) -> Result<Option<Vec<T>>, String> {
+        //! Parse a comma separated value, such as `--flag 2,10,100`, into a Vec of `T`.
+        match self.parse_value::<String>(flag_name)? {
+            Some(value) => value
+                .split(',')
+                .map(|item| {
+                    item.trim()
+                        .parse::<T>()
+                        .map_err(|_| format!("Invalid value `{}` for --{}", item, flag_name))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn require<T: FromStr>(&self, flag_name: &str) -> Result<T, String> {
+        //! Same as `parse_value`, but the flag must be present.
+        self.parse_value(flag_name)?
+            .ok_or_else(|| format!("Missing required flag --{}", flag_name))
+    }
+}
+
+///A subcommand of the runner, along with all of its flags.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    ///Solve one or both parts of a day's puzzle.
+    Run {
+        day: usize,
+        part: Option<usize>,
+        input: String,
+        flags: Flags,
+    },
+    ///Solve a part of a day's puzzle and submit the answer.
+    Submit {
+        day: usize,
+        part: usize,
+        input: String,
+        endpoint: Option<String>,
+        history: String,
+    },
+    ///Generate the module for a new day, and register it.
+    New { day: usize },
+}
+
+impl Command {
+    pub fn from_args(args: &[String]) -> Result<Command, String> {
+        //! Build a `Command` from the command line arguments, excluding the program name.
+        let (subcommand, flag_args) = args
+            .split_first()
+            .ok_or_else(|| "No subcommand given".to_owned())?;
+        let flags = Flags::parse(flag_args)?;
+        let day = flags.require::<usize>("day")?;
+        let input = flags.value("input").unwrap_or("input.txt").to_owned();
+        match subcommand.as_str() {
+            "run" => {
+                let part = flags.parse_value::<usize>("part")?;
+                if let Some(part) = part {
+                    validate_part(part)?;
+                }
+                Ok(Command::Run {
+                    day,
+                    part,
+                    input,
+                    flags,
+                })
+            }
+            "submit" => {
+                let part = flags.require::<usize>("part")?;
+                validate_part(part)?;
+                Ok(Command::Submit {
+                    day,
+                    part,
+                    input,
+                    endpoint: flags.value("endpoint").map(|endpoint| endpoint.to_owned()),
+                    history: flags
+                        .value("history")
+                        .unwrap_or("guess_history.txt")
+                        .to_owned(),
+                })
+            }
+            "new" => Ok(Command::New { day }),
+            other => Err(format!("Unknown subcommand `{}`", other)),
+        }
+    }
+}
+
+fn validate_part(part: usize) -> Result<(), String> {
+    //! Every day has exactly two parts.
+    if part == 1 || part == 2 {
+        return Ok(());
+    }
+    Err(format!("Part must be 1 or 2, got {}", part))
+}
@@ -1,24 +1,72 @@
 use crate::{read_input_file, SolveAdvent};
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
 
 pub struct Day1;
 
 impl SolveAdvent for Day1 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let mut sum_of_calibration_values = 0;
         let files_as_str = read_input_file(path_to_file);
         for line in files_as_str.lines() {
             sum_of_calibration_values += process_line_part1(line);
         }
         println!("Sum of Calibration Values: {}", sum_of_calibration_values);
+        sum_of_calibration_values.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
-        let mut sum_of_calibration_values = 0;
+    fn solve_part2(path_to_file: &str) -> String {
         let files_as_str = read_input_file(path_to_file);
-        for line in files_as_str.lines() {
-            sum_of_calibration_values += process_line_part2(line);
-        }
+        let sum_of_calibration_values =
+            sum_calibration_values(&files_as_str, &DigitMatcher::english())
+                .unwrap_or_else(|err| panic!("{}", err));
         println!("Sum of Calibration Values: {}", sum_of_calibration_values);
+        sum_of_calibration_values.to_string()
+    }
+}
+
+pub fn solve_with_words(path_to_file: &str, words_path: &str) -> Result<String, String> {
+    //! Solve part2 again, spelling the digits with the word list in the `words_path` file
+    //! instead of in English.
+    let digit_matcher = DigitMatcher::from_word_list(&read_input_file(words_path))?;
+    let files_as_str = read_input_file(path_to_file);
+    let sum_of_calibration_values = sum_calibration_values(&files_as_str, &digit_matcher)?;
+    println!(
+        "Sum of Calibration Values using {}: {}",
+        words_path, sum_of_calibration_values
+    );
+    Ok(sum_of_calibration_values.to_string())
+}
+
+pub fn explain(path_to_file: &str, part: usize) {
+    //! Print every digit found in each line, along with where it was found, and the
+    //! calibration value they make. Part1 only looks for numerals.
+    let digit_matcher = if part == 1 {
+        DigitMatcher::new(&[])
+    } else {
+        DigitMatcher::english()
+    };
+    for line in read_input_file(path_to_file).lines() {
+        let digit_matches = digit_matcher
+            .find_matches(line)
+            .iter()
+            .map(|digit_match| {
+                format!(
+                    "{}@{}",
+                    &line[digit_match.start..digit_match.end],
+                    digit_match.start
+                )
+            })
+            .collect::<Vec<_>>();
+        match digit_matcher.calibration_value(line) {
+            Some(calibration_value) => println!(
+                "{}: {} -> {}",
+                line,
+                digit_matches.join(" "),
+                calibration_value
+            ),
+            None => println!("{}: no digits", line),
+        }
     }
 }
 
@@ -48,48 +96,265 @@ fn process_line_part1(line: &str) -> usize {
         .unwrap()
 }
 
-fn get_last_digit(line: &str) -> char {
-    //! An inefficient but simple way to get the desired char.
-    let mut line_copy = line.to_owned();
-    while !line_copy.is_empty() {
-        //If the line endswith with a digit as string, return the corresponding
-        //digit.
-        for (digit_name, digit_char) in DIGIT_MAPPER {
-            if line_copy.ends_with(digit_name) {
-                return digit_char;
+///A digit found in a line, either written as a numeral or spelled out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DigitMatch {
+    ///The byte offset of the start of the match.
+    start: usize,
+    ///The byte offset just past the end of the match.
+    end: usize,
+    value: u32,
+}
+
+///A node in the trie of digit words. Every node is a prefix of at least one word.
+#[derive(Debug, Clone, Default)]
+struct MatcherNode {
+    ///The node reached by adding each next byte to this prefix.
+    children: HashMap<u8, usize>,
+    ///The node for the longest proper suffix of this prefix that is also in the trie.
+    fail: usize,
+    ///The `(length, value)` of every word ending here, including those reached through `fail`.
+    outputs: Vec<(usize, u32)>,
+}
+
+///An Aho-Corasick automaton over the digit words, which finds every digit in a line in a single
+///pass. Overlapping words are all found, like both the `eight` and the `two` in `eightwo`.
+#[derive(Debug, Clone)]
+struct DigitMatcher {
+    nodes: Vec<MatcherNode>,
+}
+
+impl DigitMatcher {
+    fn new(words: &[(String, u32)]) -> DigitMatcher {
+        //! Build the automaton for the numerals 0 to 9, plus the spelled out `words`.
+        //! A word given more than once keeps its last value.
+        let mut nodes = vec![MatcherNode::default()];
+        let numerals = (0..=9).map(|digit| (digit.to_string(), digit));
+        for (word, value) in numerals.chain(words.iter().cloned()) {
+            let mut node = 0;
+            for byte in word.bytes() {
+                node = match nodes[node].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(MatcherNode::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(byte, child);
+                        child
+                    }
+                };
             }
+            nodes[node]
+                .outputs
+                .retain(|&(length, _)| length != word.len());
+            nodes[node].outputs.push((word.len(), value));
         }
-        //Remove the last char from the string.
-        let last_char = line_copy.remove(line_copy.len() - 1);
-        if last_char.is_ascii_digit() {
-            return last_char;
+
+        //Fail links are found breadth first, so every shorter prefix already has its link.
+        //The children of the root fail back to the root.
+        let mut node_queue = nodes[0].children.values().copied().collect::<VecDeque<_>>();
+        while let Some(node) = node_queue.pop_front() {
+            let children = nodes[node]
+                .children
+                .iter()
+                .map(|(&byte, &child)| (byte, child))
+                .collect::<Vec<_>>();
+            for (byte, child) in children {
+                let fail = DigitMatcher::step(&nodes, nodes[node].fail, byte);
+                nodes[child].fail = fail;
+                let inherited_outputs = nodes[fail].outputs.clone();
+                nodes[child].outputs.extend(inherited_outputs);
+                node_queue.push_back(child);
+            }
         }
+        DigitMatcher { nodes }
     }
-    panic!("There was no trailing digit in the line!");
-}
 
-fn get_first_digit(line: &str) -> char {
-    let mut line_copy = line.to_owned();
-    while !line_copy.is_empty() {
-        //If the line starts with a digit as string, return the corresponding
-        //digit.
-        for (digit_name, digit_char) in DIGIT_MAPPER {
-            if line_copy.starts_with(digit_name) {
-                return digit_char;
+    fn english() -> DigitMatcher {
+        //! The matcher for the puzzle, with the digits one to nine spelled out in English.
+        let words = DIGIT_MAPPER
+            .iter()
+            .map(|&(digit_name, digit_char)| {
+                (digit_name.to_owned(), digit_char.to_digit(10).unwrap())
+            })
+            .collect::<Vec<_>>();
+        DigitMatcher::new(&words)
+    }
+
+    fn from_word_list(word_list: &str) -> Result<DigitMatcher, String> {
+        //! Build a matcher from a word list with one `word = value` per line, which replaces the
+        //! English words. Blank lines and lines starting with `#` are ignored, and the numerals
+        //! are always matched. For example:
+        //! ```text
+        //! zero = 0
+        //! un = 1
+        //! deux = 2
+        //! ten = 10
+        //! ```
+        let mut words = Vec::new();
+        for line in word_list.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Expected `word = value`, got `{}`", line))?;
+            let (word, value) = (word.trim(), value.trim());
+            if word.is_empty() {
+                return Err(format!("Missing the word in `{}`", line));
             }
+            let value = value
+                .parse::<u32>()
+                .map_err(|_| format!("Expected a number for `{}`, got `{}`", word, value))?;
+            words.push((word.to_owned(), value));
         }
-        //Else, remove the first char, returning if an ascii digit.
-        let first_char = line_copy.remove(0);
-        if first_char.is_ascii_digit() {
-            return first_char;
+        Ok(DigitMatcher::new(&words))
+    }
+
+    fn step(nodes: &[MatcherNode], mut node: usize, byte: u8) -> usize {
+        //! Follow the fail links until a node can be extended by `byte`, falling back to the root.
+        loop {
+            if let Some(&child) = nodes[node].children.get(&byte) {
+                return child;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = nodes[node].fail;
+        }
+    }
+
+    fn find_matches(&self, line: &str) -> Vec<DigitMatch> {
+        //! Every digit in the line, ordered by where they start, then by where they end.
+        let mut node = 0;
+        let mut digit_matches = Vec::new();
+        for (index, byte) in line.bytes().enumerate() {
+            node = DigitMatcher::step(&self.nodes, node, byte);
+            for &(length, value) in &self.nodes[node].outputs {
+                digit_matches.push(DigitMatch {
+                    start: index + 1 - length,
+                    end: index + 1,
+                    value,
+                });
+            }
         }
+        digit_matches.sort_by_key(|digit_match| (digit_match.start, digit_match.end));
+        digit_matches
+    }
+
+    fn calibration_value(&self, line: &str) -> Option<u32> {
+        //! Ten times the first digit plus the last digit. The first digit starts earliest and the
+        //! last digit ends latest, with the longer word winning a tie, so `ten` beats `te`.
+        let digit_matches = self.find_matches(line);
+        let first_digit = digit_matches
+            .iter()
+            .min_by_key(|digit_match| (digit_match.start, Reverse(digit_match.end)))?;
+        let last_digit = digit_matches
+            .iter()
+            .max_by_key(|digit_match| (digit_match.end, Reverse(digit_match.start)))?;
+        Some(first_digit.value * 10 + last_digit.value)
     }
-    panic!("Line did not contain any digits!");
 }
 
-fn process_line_part2(line: &str) -> usize {
-    let mut concatenated_digits = String::new();
-    concatenated_digits.push(get_first_digit(line));
-    concatenated_digits.push(get_last_digit(line));
-    concatenated_digits.parse::<usize>().unwrap()
+fn sum_calibration_values(file_as_str: &str, digit_matcher: &DigitMatcher) -> Result<u32, String> {
+    let mut sum_of_calibration_values = 0;
+    for (line_number, line) in file_as_str.lines().enumerate() {
+        sum_of_calibration_values += digit_matcher
+            .calibration_value(line)
+            .ok_or_else(|| format!("Line {} did not contain any digits!", line_number + 1))?;
+    }
+    Ok(sum_of_calibration_values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_part2_example() {
+        let example_input = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+        let calibration_values = example_input
+            .lines()
+            .map(|line| DigitMatcher::english().calibration_value(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(calibration_values, vec![29, 83, 13, 24, 42, 14, 76]);
+        assert_eq!(
+            sum_calibration_values(example_input, &DigitMatcher::english()),
+            Ok(281)
+        );
+        assert!(sum_calibration_values("abc", &DigitMatcher::english()).is_err());
+    }
+
+    #[test]
+    fn test_overlapping_matches() {
+        let digit_matches = DigitMatcher::english().find_matches("eightwone7");
+        assert_eq!(
+            digit_matches,
+            vec![
+                DigitMatch {
+                    start: 0,
+                    end: 5,
+                    value: 8
+                },
+                DigitMatch {
+                    start: 4,
+                    end: 7,
+                    value: 2
+                },
+                DigitMatch {
+                    start: 6,
+                    end: 9,
+                    value: 1
+                },
+                DigitMatch {
+                    start: 9,
+                    end: 10,
+                    value: 7
+                },
+            ]
+        );
+        assert_eq!(
+            DigitMatcher::english().calibration_value("xeightwo"),
+            Some(82)
+        );
+        assert_eq!(
+            DigitMatcher::new(&[]).calibration_value("two1nine"),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn test_word_list() {
+        let digit_matcher = DigitMatcher::from_word_list(
+            "# French, with zero and ten
+zéro = 0
+un = 1
+deux = 2
+trois = 3
+dix = 10
+ten = 10
+te = 4",
+        )
+        .unwrap();
+        assert_eq!(digit_matcher.calibration_value("deuxtrois"), Some(23));
+        assert_eq!(digit_matcher.calibration_value("zérodeux"), Some(2));
+        assert_eq!(digit_matcher.calibration_value("tenxdix"), Some(110));
+        assert_eq!(digit_matcher.calibration_value("two"), None);
+        assert_eq!(
+            digit_matcher.find_matches("étrois")[0],
+            DigitMatch {
+                start: 2,
+                end: 7,
+                value: 3
+            }
+        );
+        assert!(DigitMatcher::from_word_list("one").is_err());
+        assert!(DigitMatcher::from_word_list("one = first").is_err());
+        assert!(DigitMatcher::from_word_list(" = 1").is_err());
+    }
 }
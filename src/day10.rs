@@ -4,7 +4,7 @@ use std::collections::HashSet;
 pub struct Day10;
 
 impl SolveAdvent for Day10 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let pipe_map = PipeMap::new(&file_as_str);
         let (pipe_explorer1, pipe_explorer2) = pipe_map.find_two_pipes_connected_to_s();
@@ -13,46 +13,32 @@ impl SolveAdvent for Day10 {
             "The furthest distance traversal through the pipe is {}",
             furthest_distance
         );
+        furthest_distance.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let pipe_map = PipeMap::new(&file_as_str);
-        let (pipe_explorer1, pipe_explorer2) = pipe_map.find_two_pipes_connected_to_s();
-        let known_pipe_locations =
-            gather_pipe_locations(pipe_explorer1.clone(), pipe_explorer2.clone());
-
-        //Try all 4 possibilities, as it is not trivial to figure out
-        //which of the two S-connected pipes is clockwise and which is counterclockwise.
-        compute_surrounded_points(
-            pipe_explorer1.clone(),
-            &known_pipe_locations,
-            LoopDirection::Clockwise,
-        );
-        compute_surrounded_points(
-            pipe_explorer2.clone(),
-            &known_pipe_locations,
-            LoopDirection::Clockwise,
-        );
-        compute_surrounded_points(
-            pipe_explorer1,
-            &known_pipe_locations,
-            LoopDirection::Counterclockwise,
-        );
-        compute_surrounded_points(
-            pipe_explorer2,
-            &known_pipe_locations,
-            LoopDirection::Counterclockwise,
-        );
+        let enclosed_tile_count = compute_enclosed_tile_count(&pipe_map);
+        println!("The loop encloses {} tiles", enclosed_tile_count);
+        enclosed_tile_count.to_string()
     }
 }
 
-///Stores the Input Map and
-/// the position of S as a row, col
+pub fn render(path_to_file: &str, use_color: bool) {
+    //! Print the map, highlighting the loop and the tiles it encloses.
+    let file_as_str = read_input_file(path_to_file);
+    let pipe_map = PipeMap::new(&file_as_str);
+    print!("{}", render_pipe_map(&pipe_map, use_color));
+}
+
+///Stores the Input Map,
+/// the position of S as a row, col, and the pipe hidden under S.
 #[derive(Debug, Clone)]
 struct PipeMap {
     map: Vec<Vec<char>>,
     s_position: (usize, usize),
+    s_pipe: char,
 }
 
 impl PipeMap {
@@ -75,12 +61,35 @@ impl PipeMap {
             .collect::<Vec<_>>();
 
         let s_position = PipeMap::find_s_position(&map);
-        PipeMap { map, s_position }
+        let mut pipe_map = PipeMap {
+            map,
+            s_position,
+            s_pipe: 'S',
+        };
+        pipe_map.s_pipe = pipe_map.infer_s_pipe();
+        pipe_map
+    }
+
+    fn infer_s_pipe(&self) -> char {
+        //! The pipe under S is the one that connects the two pipes connected to S.
+        let (pipe_explorer1, pipe_explorer2) = self.find_two_pipes_connected_to_s();
+        match (&pipe_explorer1.direction, &pipe_explorer2.direction) {
+            (Direction::Up, Direction::Down) => '|',
+            (Direction::Right, Direction::Left) => '-',
+            (Direction::Up, Direction::Right) => 'L',
+            (Direction::Up, Direction::Left) => 'J',
+            (Direction::Left, Direction::Down) => '7',
+            (Direction::Right, Direction::Down) => 'F',
+            _ => panic!("S does not connect two pipes"),
+        }
     }
 
     fn get_pipe_value(&self, row: usize, col: usize) -> Option<&char> {
         //! Return the value in the map corresponding to the passed in row, col
-        //! if it exists
+        //! if it exists. The position of S returns the pipe hidden under it.
+        if (row, col) == self.s_position {
+            return Some(&self.s_pipe);
+        }
         if let Some(row) = self.map.get(row) {
             if let Some(pipe_symbol) = row.get(col) {
                 return Some(pipe_symbol);
@@ -89,7 +98,7 @@ impl PipeMap {
         None
     }
 
-    fn find_two_pipes_connected_to_s(&self) -> (PipeExplorer, PipeExplorer) {
+    fn find_two_pipes_connected_to_s(&self) -> (PipeExplorer<'_>, PipeExplorer<'_>) {
         //! Start from the position of S, look up, down, left, right.
         //! The prompt gurantees that exactly 2 pipes connected to S form the pipe
         //! loop.
@@ -160,6 +169,17 @@ impl PipeMap {
             valid_starting_positions.get(1).unwrap().clone(),
         )
     }
+
+    fn trace_loop(&self) -> Vec<(usize, usize)> {
+        //! The position of every pipe in the loop, in the order they are traveled, starting from S.
+        let (mut pipe_explorer, _) = self.find_two_pipes_connected_to_s();
+        let mut loop_positions = vec![self.s_position];
+        while (pipe_explorer.row, pipe_explorer.col) != self.s_position {
+            loop_positions.push((pipe_explorer.row, pipe_explorer.col));
+            pipe_explorer.move_next();
+        }
+        loop_positions
+    }
 }
 
 ///The four possible directions an explorer
@@ -201,43 +221,6 @@ impl<'a> PipeExplorer<'a> {
             Direction::Left => (self.row, self.col - 1),
         }
     }
-    fn get_left_orthogonal(&self) -> Option<(usize, usize)> {
-        //! Given an Explorer traversing the loop, return the point directly
-        //! left orthogonal to the direction of motion of the pipe.
-        //!
-        //! This could be impossible
-        //! if the wrong Explorer has been chosen for the given loop traversal direction. For example,
-        //! if Explorer1 is the true Clockwise explorer but Explorer2 is attempted, then it is possible
-        //! that the left orthoginal goes off the map, in which case None is returned, terminating loop
-        //! iteration.
-        match self.direction {
-            Direction::Down if self.col < self.pipe_map.map[0].len() - 1 => {
-                Some((self.row, self.col + 1))
-            }
-            Direction::Left if self.row < self.pipe_map.map.len() - 1 => {
-                Some((self.row + 1, self.col))
-            }
-            Direction::Right if self.row > 0 => Some((self.row - 1, self.col)),
-            Direction::Up if self.col > 0 => Some((self.row, self.col - 1)),
-            _ => None,
-        }
-    }
-    fn get_right_orthogonal(&self) -> Option<(usize, usize)> {
-        //! Return the right orthogonal point of the current `PipeExplorer`.
-        //! It is possible that this will fail because the right orthogonal point is off the map.
-        match self.direction {
-            Direction::Down if self.col > 0 => Some((self.row, self.col - 1)),
-            Direction::Left if self.row > 0 => Some((self.row - 1, self.col)),
-            Direction::Right if self.row < self.pipe_map.map.len() - 1 => {
-                Some((self.row + 1, self.col))
-            }
-            Direction::Up if self.col < self.pipe_map.map[0].len() - 1 => {
-                Some((self.row, self.col + 1))
-            }
-            _ => None,
-        }
-    }
-
     fn change_direction(&mut self) {
         //! Following the traversal rules of the Day10 problem,
         //! move, change the direction of motion of the `PipeExplorer`.
@@ -300,127 +283,226 @@ fn find_largest_distance_from_s(mut pipe_1: PipeExplorer, mut pipe_2: PipeExplor
     pipe_1.steps.max(pipe_2.steps)
 }
 
-fn gather_pipe_locations(
-    mut pipe_1: PipeExplorer,
-    mut pipe_2: PipeExplorer,
+fn compute_enclosed_tile_count(pipe_map: &PipeMap) -> usize {
+    //! Solves Part2 of the Day10 challenge. The corners of the loop, in order, are the vertices of a polygon
+    //! whose area is given by the shoelace formula. Pick's theorem relates that area to the number
+    //! of tiles on the loop b, and the number of tiles enclosed by it i: `A = i + b/2 - 1`.
+    let loop_positions = pipe_map.trace_loop();
+    let vertices = loop_positions
+        .iter()
+        .filter(|(row, col)| {
+            let pipe_symbol = pipe_map.get_pipe_value(*row, *col).unwrap();
+            ['L', 'J', '7', 'F'].contains(pipe_symbol)
+        })
+        .map(|&(row, col)| (row as i64, col as i64))
+        .collect::<Vec<_>>();
+    let twice_area = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(&(row1, col1), &(row2, col2))| col1 * row2 - col2 * row1)
+        .sum::<i64>()
+        .unsigned_abs() as usize;
+    //Rearranging Pick's theorem, i = A - b/2 + 1. Adding before subtracting keeps a loop
+    //that encloses nothing, where 2A = b - 2, from underflowing.
+    (twice_area + 2 - loop_positions.len()) / 2
+}
+
+fn find_enclosed_tiles(
+    pipe_map: &PipeMap,
+    loop_positions: &HashSet<(usize, usize)>,
 ) -> HashSet<(usize, usize)> {
-    //! Gather a HashSet containing the ordered pair positions
-    //! of all pipes in the actual loop attached to S.
-    let mut pipe_locations = HashSet::new();
-    pipe_locations.insert(pipe_1.pipe_map.s_position);
-    loop {
-        pipe_locations.insert((pipe_1.row, pipe_1.col));
-        pipe_locations.insert((pipe_2.row, pipe_2.col));
-        pipe_1.move_next();
-        if pipe_1.collided(&pipe_2) {
-            break;
-        };
-        pipe_2.move_next();
-        if pipe_1.collided(&pipe_2) {
-            break;
+    //! Scan each row from left to right. Every time a loop pipe that connects upwards is passed,
+    //! the scan crosses the loop, and so switches between being outside and inside the loop.
+    let mut enclosed_tiles = HashSet::new();
+    for (row_number, row) in pipe_map.map.iter().enumerate() {
+        let mut inside = false;
+        for col_number in 0..row.len() {
+            if loop_positions.contains(&(row_number, col_number)) {
+                let pipe_symbol = pipe_map.get_pipe_value(row_number, col_number).unwrap();
+                if ['|', 'L', 'J'].contains(pipe_symbol) {
+                    inside = !inside;
+                }
+            } else if inside {
+                enclosed_tiles.insert((row_number, col_number));
+            }
         }
     }
-    pipe_locations.insert((pipe_1.row, pipe_1.col));
-    pipe_locations.insert((pipe_2.row, pipe_2.col));
-    pipe_locations
+    enclosed_tiles
 }
 
-fn walk_loop_interior(
-    known_pipe_locations: &HashSet<(usize, usize)>,
-    starting_point: (usize, usize),
-    interior_points_visited: &mut HashSet<(usize, usize)>,
-    map_dimensions: (usize, usize),
-) -> Option<()> {
-    //! Given a starting point, traverse all points in every
-    //! direction, stopping traversal when a known pipe is reached, or an
-    //! interior_point already visited.
-    let mut stack = vec![starting_point];
-    while let Some(current_position) = stack.pop() {
-        if interior_points_visited.contains(&current_position)
-            || known_pipe_locations.contains(&current_position)
-        {
-            continue;
-        }
-        interior_points_visited.insert(current_position);
-        //protects against accidentally going off the map.
-        let next_steps_are_ok = current_position.0 < map_dimensions.0
-            && current_position.0 > 0
-            && current_position.1 < map_dimensions.1
-            && current_position.1 > 0;
-        if !next_steps_are_ok {
-            return None;
+fn render_pipe_map(pipe_map: &PipeMap, use_color: bool) -> String {
+    //! Redraw the map with box-drawing characters. The loop is drawn with heavy lines, and
+    //! pipes that are not on the loop with light lines. Enclosed tiles are marked with an I.
+    //! With color, pipes off the loop are also dimmed, enclosed tiles are green, and S is yellow.
+    const DIM: &str = "\x1b[2m";
+    const GREEN: &str = "\x1b[32m";
+    const BOLD_YELLOW: &str = "\x1b[1;33m";
+    const RESET: &str = "\x1b[0m";
+    let loop_positions = pipe_map.trace_loop().into_iter().collect::<HashSet<_>>();
+    let enclosed_tiles = find_enclosed_tiles(pipe_map, &loop_positions);
+    let mut rendered = String::new();
+    for (row_number, row) in pipe_map.map.iter().enumerate() {
+        for col_number in 0..row.len() {
+            let position = (row_number, col_number);
+            let pipe_symbol = *pipe_map.get_pipe_value(row_number, col_number).unwrap();
+            let (tile, color) = if loop_positions.contains(&position) {
+                let color = if position == pipe_map.s_position {
+                    BOLD_YELLOW
+                } else {
+                    ""
+                };
+                (heavy_box_drawing(pipe_symbol), color)
+            } else if enclosed_tiles.contains(&position) {
+                ('I', GREEN)
+            } else {
+                (light_box_drawing(pipe_symbol), DIM)
+            };
+            if use_color && !color.is_empty() {
+                rendered.push_str(color);
+                rendered.push(tile);
+                rendered.push_str(RESET);
+            } else {
+                rendered.push(tile);
+            }
         }
-        let next_positions = [
-            (current_position.0 + 1, current_position.1),
-            (current_position.0 - 1, current_position.1),
-            (current_position.0, current_position.1 + 1),
-            (current_position.0, current_position.1 - 1),
-        ];
-        stack.extend(next_positions);
+        rendered.push('\n');
     }
-    Some(())
+    rendered
 }
 
-///The loop can be traversed Clockwise or Counterclockwise.
-#[derive(Debug, Clone)]
-enum LoopDirection {
-    Clockwise,
-    Counterclockwise,
+fn heavy_box_drawing(pipe_symbol: char) -> char {
+    match pipe_symbol {
+        '|' => '┃',
+        '-' => '━',
+        'L' => '┗',
+        'J' => '┛',
+        '7' => '┓',
+        'F' => '┏',
+        other => other,
+    }
 }
 
-fn compute_surrounded_points(
-    mut explorer_guess: PipeExplorer,
-    pipe_locations: &HashSet<(usize, usize)>,
-    mode: LoopDirection,
-) -> Option<()> {
-    //! Solves Part2 of the Day10 challenge. The key insight here is that
-    //! points that are 'actually in the loop' will always be reachable from the right side
-    //! of the loop if traveling clockwise, and from the left side if traveling counterclockwise.
-    //!
-    //! To solve the problem, traverse the loop either clockwise or counterclockwise.
-    //! For each loop point reached, traverse the points to the right or left orthogonal if possible
-    //! (stop if another pipe is reached, or if another traversal has already reached the point).
-    //! Because it is not trivial to determine which pipe connected to S is the clockwise and counterclockwise pipes,
-    //! all 4 possibilities are tried. This means that 2 invocations of this function will return None, and 2 will produce values.
-    //! The values for clockwise and counterclockwise traversal should be identical, they are both computed simply for validation.
-    let map_dimensions = (
-        explorer_guess.pipe_map.map.len(),
-        explorer_guess.pipe_map.map[0].len(),
-    );
-    let (s_position_row, s_position_col) = explorer_guess.pipe_map.s_position;
-    let mut found_surrounded_tiles = HashSet::new();
-    while !(explorer_guess.row == s_position_row && explorer_guess.col == s_position_col) {
-        //The Interior must be walked twice for each explorer position, once
-        //before and once after changing the direction of the explorer. Otherwise, a small number
-        //of edge cases are missed in the count.
-        let orthogonal_point = match mode {
-            LoopDirection::Clockwise => explorer_guess.get_right_orthogonal()?,
-            LoopDirection::Counterclockwise => explorer_guess.get_left_orthogonal()?,
-        };
-        walk_loop_interior(
-            pipe_locations,
-            orthogonal_point,
-            &mut found_surrounded_tiles,
-            map_dimensions,
-        )?;
-        //Change the explorers direction and compute the surrounded tiles a second time.
-        explorer_guess.change_direction();
-        let orthogonal_point = match mode {
-            LoopDirection::Clockwise => explorer_guess.get_right_orthogonal()?,
-            LoopDirection::Counterclockwise => explorer_guess.get_left_orthogonal()?,
-        };
-        walk_loop_interior(
-            pipe_locations,
-            orthogonal_point,
-            &mut found_surrounded_tiles,
-            map_dimensions,
-        )?;
-        explorer_guess.move_in_new_direction();
+fn light_box_drawing(pipe_symbol: char) -> char {
+    match pipe_symbol {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        '.' => ' ',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_furthest_distance() {
+        let pipe_map = PipeMap::new(
+            "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...",
+        );
+        let (pipe_explorer1, pipe_explorer2) = pipe_map.find_two_pipes_connected_to_s();
+        assert_eq!(
+            find_largest_distance_from_s(pipe_explorer1, pipe_explorer2),
+            8
+        );
+        assert_eq!(pipe_map.trace_loop().len(), 16);
+    }
+
+    #[test]
+    fn test_infer_s_pipe() {
+        assert_eq!(PipeMap::new("S-7\n|.|\nL-J").s_pipe, 'F');
+        assert_eq!(PipeMap::new("F-S\n|.|\nL-J").s_pipe, '7');
+        assert_eq!(PipeMap::new("F-7\n|.|\nS-J").s_pipe, 'L');
+        assert_eq!(PipeMap::new("F-7\n|.|\nL-S").s_pipe, 'J');
+        assert_eq!(PipeMap::new("F7\n|S\nLJ").s_pipe, '|');
+        assert_eq!(PipeMap::new("FS7\nL-J").s_pipe, '-');
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        let examples = [
+            (
+                "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+                4,
+            ),
+            (
+                ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+                8,
+            ),
+            (
+                "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+                10,
+            ),
+            //The smallest loop, which encloses no tiles.
+            ("S7\nLJ", 0),
+            ("F--S\nL--J", 0),
+        ];
+        for (map, enclosed_tile_count) in examples {
+            let pipe_map = PipeMap::new(map);
+            assert_eq!(compute_enclosed_tile_count(&pipe_map), enclosed_tile_count);
+            let loop_positions = pipe_map.trace_loop().into_iter().collect::<HashSet<_>>();
+            assert_eq!(
+                find_enclosed_tiles(&pipe_map, &loop_positions).len(),
+                enclosed_tile_count
+            );
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let pipe_map = PipeMap::new(
+            "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF",
+        );
+        assert_eq!(
+            render_pipe_map(&pipe_map, false),
+            "─└│┌┐
+┐┏━┓│
+└┃I┃│
+─┗━┛│
+└│─┘┌
+"
+        );
+        let colored = render_pipe_map(&pipe_map, true);
+        assert!(colored.contains("\x1b[1;33m┏\x1b[0m"));
+        assert!(colored.contains("\x1b[32mI\x1b[0m"));
+        assert!(colored.starts_with("\x1b[2m─\x1b[0m"));
     }
-    println!(
-        "Surrounded tile count is {} when traveling the loop {:?}",
-        found_surrounded_tiles.len(),
-        mode
-    );
-    Some(())
 }
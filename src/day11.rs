@@ -1,41 +1,81 @@
 use crate::{read_input_file, SolveAdvent};
-use std::collections::{HashSet, VecDeque};
 
 pub struct Day11;
 
 impl SolveAdvent for Day11 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let universe = Universe::new(&file_as_str, 2);
-        universe.find_minimum_distance_between_all_galaxies();
+        let universe = Universe::new(&file_as_str);
+        universe
+            .find_minimum_distance_between_all_galaxies(2)
+            .to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let universe = Universe::new(&file_as_str, 1_000_000);
-        universe.find_minimum_distance_between_all_galaxies();
+        let universe = Universe::new(&file_as_str);
+        universe
+            .find_minimum_distance_between_all_galaxies(1_000_000)
+            .to_string()
     }
 }
 
+pub fn report_distances(
+    path_to_file: &str,
+    scale_factors: &[usize],
+    between: Option<&[usize]>,
+) -> Result<(), String> {
+    //! Print the sum of the distances between all galaxies for every scale factor. If two galaxy
+    //! numbers are passed in `between`, also print the distance between those two galaxies.
+    if scale_factors.contains(&0) {
+        return Err("Scale factors must be at least 1".to_owned());
+    }
+    let file_as_str = read_input_file(path_to_file);
+    let universe = Universe::new(&file_as_str);
+    let distance_sums = universe.distance_sums(scale_factors);
+    for (scale_factor, distance_sum) in scale_factors.iter().zip(distance_sums) {
+        println!(
+            "Total minimum distances between galaxies with scale factor {} is: {}",
+            scale_factor, distance_sum
+        );
+    }
+    if let Some(between) = between {
+        let [galaxy_number_1, galaxy_number_2]: [usize; 2] = between
+            .try_into()
+            .map_err(|_| "Exactly two galaxy numbers are needed".to_owned())?;
+        for &scale_factor in scale_factors {
+            let distance = universe
+                .distance_between(galaxy_number_1, galaxy_number_2, scale_factor)
+                .ok_or_else(|| {
+                    format!(
+                        "There are only {} galaxies in this universe",
+                        universe.galaxies.len()
+                    )
+                })?;
+            println!(
+                "Distance between galaxy {} and galaxy {} with scale factor {} is: {}",
+                galaxy_number_1, galaxy_number_2, scale_factor, distance
+            );
+        }
+    }
+    Ok(())
+}
+
 ///A Galaxy is a '#' on the input map.
 #[derive(Debug, Clone, Copy)]
 struct Galaxy {
-    ///Unique ID for the galaxy.
-    id: usize,
     row: usize,
     col: usize,
 }
 
 impl Galaxy {
     fn identify_galaxies(universe_map: &[Vec<char>]) -> Vec<Galaxy> {
-        //! Process the Universe, taking note of the position of all Galaxies.
+        //! Process the Universe, taking note of the position of all Galaxies in reading order.
         let mut identified_galaxies = Vec::new();
         for (row_number, row) in universe_map.iter().enumerate() {
             for (column_number, col_value) in row.iter().enumerate() {
                 if col_value == &'#' {
-                    let galaxy_number = identified_galaxies.len();
                     identified_galaxies.push(Galaxy {
-                        id: galaxy_number,
                         row: row_number,
                         col: column_number,
                     });
@@ -50,46 +90,57 @@ impl Galaxy {
 #[derive(Debug, Clone)]
 struct Universe {
     ///The column numbers that are expanded because
-    /// they have only '.'
-    expanded_columns: HashSet<usize>,
-    ///The row numbers that are expanded because they have only '.'
-    expanded_rows: HashSet<usize>,
-    ///A nod to cosmology. How much each expanded row or column is expanded by.
-    scale_factor: usize,
+    /// they have only '.', in ascending order.
+    expanded_columns: Vec<usize>,
+    ///The row numbers that are expanded because they have only '.', in ascending order.
+    expanded_rows: Vec<usize>,
     /// All of the galaxies in this Universe.
     galaxies: Vec<Galaxy>,
-    /// The dimensions of the universe
-    dimensions: (usize, usize),
+}
+
+///The distances between every pair of galaxies, split into the part that does not
+/// depend on the scale factor, and the part that does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DistanceTotals {
+    ///The total distance if the universe did not expand at all.
+    unexpanded: usize,
+    ///The total number of expanded rows and columns crossed. Each of these is
+    /// crossed in scale factor steps instead of 1.
+    expanded_crossings: usize,
+}
+
+impl DistanceTotals {
+    fn with_scale_factor(&self, scale_factor: usize) -> usize {
+        //! The total distance once every expanded row and column is `scale_factor` wide.
+        self.unexpanded + self.expanded_crossings * (scale_factor - 1)
+    }
 }
 
 impl Universe {
-    fn new(file_as_str: &str, scale_factor: usize) -> Universe {
+    fn new(file_as_str: &str) -> Universe {
         //! Constructs a new Universe.
         let galaxies_disassembled = file_as_str
             .lines()
             .map(|line| line.chars().collect::<Vec<_>>())
             .collect::<Vec<_>>();
 
-        //Take note of the dimensions of the universe
-        let universe_dimensions = (galaxies_disassembled.len(), galaxies_disassembled[0].len());
-
         //Take note of the row numbers that are expanded
-        let mut expanded_rows = HashSet::new();
+        let mut expanded_rows = Vec::new();
         for (row_number, row_content) in galaxies_disassembled.iter().enumerate() {
             if row_content.iter().all(|char| char == &'.') {
-                expanded_rows.insert(row_number);
+                expanded_rows.push(row_number);
             }
         }
 
         //Take note of the column numbers that are expanded.
-        let mut expanded_columns = HashSet::new();
+        let mut expanded_columns = Vec::new();
         let mut column_number = 0;
         while column_number < galaxies_disassembled[0].len() {
             let mut column_content = galaxies_disassembled
                 .iter()
                 .map(|row| row.get(column_number).unwrap());
             if column_content.all(|char| char == &'.') {
-                expanded_columns.insert(column_number);
+                expanded_columns.push(column_number);
             }
             column_number += 1;
         }
@@ -97,246 +148,160 @@ impl Universe {
         let galaxies = Galaxy::identify_galaxies(&galaxies_disassembled);
 
         println!(
-            "------------There are {} total galaxies in this universe with scale factor: {}-------------",
-            galaxies.len(), 
-            scale_factor
+            "------------There are {} total galaxies in this universe-------------",
+            galaxies.len()
         );
 
         Universe {
             expanded_columns,
             expanded_rows,
-            scale_factor,
-            dimensions: universe_dimensions,
             galaxies,
         }
     }
 
-    fn find_minimum_distance_between_all_galaxies(&self) {
+    fn find_minimum_distance_between_all_galaxies(&self, scale_factor: usize) -> usize {
         //! Perform the actual computation required of Day11. Find the sum of the shortest distance
         //! between each unique pair of galaxies.
-        let galaxy_pairs = self.unique_galaxy_pairs();
-        println!("Found {} unique galaxy pairs", galaxy_pairs.len());
-        let mut total_minimum_distance = 0;
-        for (galaxy_1_id, galaxy_2_id) in galaxy_pairs.into_iter() {
-            let galaxy_1 = self.galaxies.get(galaxy_1_id).unwrap();
-            let galaxy_2 = self.galaxies.get(galaxy_2_id).unwrap();
-            let minimum_distance = find_minimum_distance(galaxy_1, galaxy_2, self);
-            total_minimum_distance += minimum_distance;
-        }
+        let total_minimum_distance = self.distance_totals().with_scale_factor(scale_factor);
         println!(
-            "Total minimum distances between galaxies is: {}",
-            total_minimum_distance
+            "Total minimum distances between galaxies with scale factor {} is: {}",
+            scale_factor, total_minimum_distance
         );
+        total_minimum_distance
     }
 
-    fn unique_galaxy_pairs(&self) -> HashSet<(usize, usize)> {
-        //! Determine the minimum set of unique galaxy pairs that must be investigated, as order does
-        //! not matter.
-        //! The easiest way to do this is to simply iterate twice through the same array, and
-        //! only log pairs when the id of galaxy_1 is less than the id of galaxy_2.
-        let mut seen_pairs = HashSet::new();
-        for galaxy1 in self.galaxies.iter() {
-            for galaxy_2 in self.galaxies.iter() {
-                if galaxy1.id < galaxy_2.id {
-                    seen_pairs.insert((galaxy1.id, galaxy_2.id));
-                    if seen_pairs.contains(&(galaxy_2.id, galaxy1.id)) {
-                        panic!(
-                            "Pair {:?} is in the seen pairs as we insert the reverse!",
-                            (galaxy_2.id, galaxy1.id)
-                        );
-                    }
-                }
-            }
-        }
-        seen_pairs
+    fn distance_sums(&self, scale_factors: &[usize]) -> Vec<usize> {
+        //! The sum of the distances between each unique pair of galaxies, for every scale factor.
+        //! The galaxies are only processed once, however many scale factors there are.
+        let distance_totals = self.distance_totals();
+        scale_factors
+            .iter()
+            .map(|&scale_factor| distance_totals.with_scale_factor(scale_factor))
+            .collect::<Vec<_>>()
     }
-}
 
-///A probe is trying to roam the Universe
-/// and find the target.
-#[derive(Debug, Clone, Copy)]
-struct Probe<'a> {
-    row: usize,
-    col: usize,
-    distance_traveled: usize,
-    target_row: usize,
-    target_col: usize,
-    universe: &'a Universe,
-}
-
-impl<'a> Probe<'a> {
-    fn target_found(&self) -> bool {
-        //! Has the probe found the target?
-        self.col == self.target_col && self.row == self.target_row
-    }
-    fn distance_to_target(&self) -> f64 {
-        //! Apply the straightforward definition of distance to determine how far
-        //! away the probe is from the target. This is an essential optimization to allow
-        //! for each probe to only move in the directions the minimizes the distance.
-        //!
-        //! It is a non-trivial fact to note that the distance without taking into
-        //! account the expansion of the universe can be used here. The shortest path from
-        //! one galaxy to another is still the same regardless of the universe scaling, only the distance
-        //! traveled is variable based on scale factor. THIS WOULD NOT BE TRUE IF THE EXPANSION WERE IRREGULAR!
-        let distance_x = self.target_row as f64 - self.row as f64;
-        let distance_y = self.target_col as f64 - self.col as f64;
-        ((distance_x).powf(2.0) + (distance_y).powf(2.0)).sqrt()
-    }
-    fn left_one(&self) -> Option<Probe<'a>> {
-        //! Attempt to get a new probe the moves left. This will return None
-        //! if going left takes the probe outside of the bounds of the Universe or if moving
-        //! left increases the distance to the target.
-        if self.col == 0 {
-            return None;
-        }
-        //If the probe is crossing an `expanded` column, then the distance traveled
-        //needs to be increased by the scale factor instead of 1.
-        let distance_this_step = if self.universe.expanded_columns.contains(&self.col) {
-            self.universe.scale_factor
-        } else {
-            1
-        };
-        let left_one = Probe {
-            row: self.row,
-            col: self.col - 1,
-            distance_traveled: self.distance_traveled + distance_this_step,
-            target_col: self.target_col,
-            target_row: self.target_row,
-            universe: self.universe,
-        };
-        if left_one.distance_to_target() > self.distance_to_target() {
-            return None;
+    fn distance_totals(&self) -> DistanceTotals {
+        //! Because the shortest path between two galaxies only ever moves towards the target,
+        //! the distance is the sum of the row and column distances, and each axis can be handled alone.
+        //! For each axis, sum the differences between every pair of positions, and every pair of
+        //! counts of expanded lines before each position.
+        let rows = self
+            .galaxies
+            .iter()
+            .map(|galaxy| galaxy.row)
+            .collect::<Vec<_>>();
+        let cols = self
+            .galaxies
+            .iter()
+            .map(|galaxy| galaxy.col)
+            .collect::<Vec<_>>();
+        let expanded_rows_before = rows
+            .iter()
+            .map(|&row| expanded_before(&self.expanded_rows, row))
+            .collect::<Vec<_>>();
+        let expanded_cols_before = cols
+            .iter()
+            .map(|&col| expanded_before(&self.expanded_columns, col))
+            .collect::<Vec<_>>();
+        DistanceTotals {
+            unexpanded: sum_of_pairwise_differences(rows) + sum_of_pairwise_differences(cols),
+            expanded_crossings: sum_of_pairwise_differences(expanded_rows_before)
+                + sum_of_pairwise_differences(expanded_cols_before),
         }
-        Some(left_one)
     }
 
-    fn right_one(&self) -> Option<Probe<'a>> {
-        //! Attempt to get a probe one to the right.
-        let universe_column_boundary = self.universe.dimensions.1;
-        if self.col + 1 >= universe_column_boundary {
-            return None;
-        }
-        //If the probe is crossing an `expanded` column, then the distance traveled
-        //needs to be increased by the scale factor instead of 1.
-        let distance_this_step = if self.universe.expanded_columns.contains(&self.col) {
-            self.universe.scale_factor
-        } else {
-            1
-        };
-        let right_one = Probe {
-            row: self.row,
-            col: self.col + 1,
-            distance_traveled: self.distance_traveled + distance_this_step,
-            target_col: self.target_col,
-            target_row: self.target_row,
-            universe: self.universe,
+    fn distance_between(
+        &self,
+        galaxy_number_1: usize,
+        galaxy_number_2: usize,
+        scale_factor: usize,
+    ) -> Option<usize> {
+        //! The shortest distance between two galaxies, numbered from 1 in reading order as the
+        //! problem does. Returns None if either galaxy does not exist.
+        let galaxy_1 = self.galaxies.get(galaxy_number_1.checked_sub(1)?)?;
+        let galaxy_2 = self.galaxies.get(galaxy_number_2.checked_sub(1)?)?;
+        let expanded_distance = |expanded_lines: &[usize], position_1: usize, position_2: usize| {
+            let expanded_crossings = expanded_before(expanded_lines, position_1)
+                .abs_diff(expanded_before(expanded_lines, position_2));
+            position_1.abs_diff(position_2) + expanded_crossings * (scale_factor - 1)
         };
-        if right_one.distance_to_target() > self.distance_to_target() {
-            return None;
-        }
-        Some(right_one)
+        Some(
+            expanded_distance(&self.expanded_rows, galaxy_1.row, galaxy_2.row)
+                + expanded_distance(&self.expanded_columns, galaxy_1.col, galaxy_2.col),
+        )
     }
+}
 
-    fn up_one(&self) -> Option<Probe<'a>> {
-        //! Attempt to get a probe one up, which may fail.
-        if self.row == 0 {
-            return None;
-        }
-        //If the current probe is crossing an expanded row, then the distance traveled
-        //in this step is the scale factor rather than 1.
-        let distance_this_step = if self.universe.expanded_rows.contains(&self.row) {
-            self.universe.scale_factor
-        } else {
-            1
-        };
-        let up_one = Probe {
-            row: self.row - 1,
-            col: self.col,
-            distance_traveled: self.distance_traveled + distance_this_step,
-            target_col: self.target_col,
-            target_row: self.target_row,
-            universe: self.universe,
-        };
-        if up_one.distance_to_target() > self.distance_to_target() {
-            return None;
-        }
-        Some(up_one)
-    }
+fn expanded_before(expanded_lines: &[usize], position: usize) -> usize {
+    //! The number of expanded lines before the position, using a binary search of the sorted lines.
+    expanded_lines.partition_point(|&line| line < position)
+}
 
-    fn down_one(&self) -> Option<Probe<'a>> {
-        //! Attempt to get a probe one down. This may fail for various reasons.
-        let row_boundary = self.universe.dimensions.0;
-        if self.row + 1 >= row_boundary {
-            return None;
-        }
-        //If the current probe is crossing an expanded row, then the distance traveled
-        //in this step is the scale factor rather than 1.
-        let distance_this_step = if self.universe.expanded_rows.contains(&self.row) {
-            self.universe.scale_factor
-        } else {
-            1
-        };
-        let down_one = Probe {
-            row: self.row + 1,
-            col: self.col,
-            distance_traveled: self.distance_traveled + distance_this_step,
-            target_col: self.target_col,
-            target_row: self.target_row,
-            universe: self.universe,
-        };
-        if down_one.distance_to_target() > self.distance_to_target() {
-            return None;
-        }
-        Some(down_one)
+fn sum_of_pairwise_differences(mut values: Vec<usize>) -> usize {
+    //! Once sorted, each value is larger than every value before it, so it contributes
+    //! `value * (number of values before) - (sum of values before)` to the total.
+    values.sort_unstable();
+    let mut prefix_sum = 0;
+    let mut total = 0;
+    for (values_before, value) in values.into_iter().enumerate() {
+        total += value * values_before - prefix_sum;
+        prefix_sum += value;
     }
+    total
 }
 
-fn find_minimum_distance(galaxy_1: &Galaxy, galaxy_2: &Galaxy, universe: &Universe) -> usize {
-    //! Use breadth first search to traverse the universe, using probes. Breadth first search is used
-    //! so that as soon as a single probe finds the target, the distance traveled by the probe is guranteed to be
-    //! the minimum distance.
-    //!
-    //! To optimize the performance, of the 4 possible probe movement directions, only the direction
-    //! that moves the probe closest to the target is tested. Because of this optimization, we do not need
-    //! a hashset to protect against cycles. The probe traversal will never get stuck in a cycle because each iteration
-    //! it only moves in the optimal direction.
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    //Use a VecDeque for efficient breadth first search.
-    let mut probes_tracker = VecDeque::new();
+    const EXAMPLE_INPUT: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
 
-    //The first probe starts at Galaxy_1, with Galaxy_2 as its target.
-    probes_tracker.push_back(Probe {
-        row: galaxy_1.row,
-        col: galaxy_1.col,
-        distance_traveled: 0,
-        target_col: galaxy_2.col,
-        target_row: galaxy_2.row,
-        universe,
-    });
+    #[test]
+    fn test_distance_sums() {
+        let universe = Universe::new(EXAMPLE_INPUT);
+        assert_eq!(universe.distance_sums(&[2, 10, 100]), vec![374, 1030, 8410]);
+        assert_eq!(universe.find_minimum_distance_between_all_galaxies(2), 374);
+    }
 
-    while !probes_tracker.is_empty() {
-        let latest_probe = probes_tracker.pop_front().unwrap();
-        if latest_probe.target_found() {
-            return latest_probe.distance_traveled;
+    #[test]
+    fn test_distance_between() {
+        let universe = Universe::new(EXAMPLE_INPUT);
+        assert_eq!(universe.distance_between(5, 9, 2), Some(9));
+        assert_eq!(universe.distance_between(1, 7, 2), Some(15));
+        assert_eq!(universe.distance_between(3, 6, 2), Some(17));
+        assert_eq!(universe.distance_between(8, 9, 2), Some(5));
+        assert_eq!(universe.distance_between(0, 9, 2), None);
+        assert_eq!(universe.distance_between(1, 10, 2), None);
+        //The sums agree with adding up the distance between every pair.
+        for scale_factor in [1, 2, 10, 1_000_000] {
+            let mut pairwise_total = 0;
+            for galaxy_number_1 in 1..=9 {
+                for galaxy_number_2 in galaxy_number_1 + 1..=9 {
+                    pairwise_total += universe
+                        .distance_between(galaxy_number_1, galaxy_number_2, scale_factor)
+                        .unwrap();
+                }
+            }
+            assert_eq!(
+                universe.distance_sums(&[scale_factor]),
+                vec![pairwise_total]
+            );
         }
-
-        let mut next_probes = [
-            latest_probe.left_one(),
-            latest_probe.right_one(),
-            latest_probe.up_one(),
-            latest_probe.down_one(),
-        ]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-        next_probes.sort_by(|a, b| {
-            a.distance_to_target()
-                .partial_cmp(&b.distance_to_target())
-                .unwrap()
-        });
-        let next_probe = next_probes[0];
-        probes_tracker.push_back(next_probe);
     }
 
-    panic!("No shortest path was found between galaxies, which is impossible!");
+    #[test]
+    fn test_sum_of_pairwise_differences() {
+        assert_eq!(sum_of_pairwise_differences(vec![5, 1, 3]), 8);
+        assert_eq!(sum_of_pairwise_differences(vec![4, 4]), 0);
+        assert_eq!(sum_of_pairwise_differences(Vec::new()), 0);
+    }
 }
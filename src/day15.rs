@@ -1,45 +1,69 @@
 use crate::{read_input_file, SolveAdvent};
 use std::collections::HashMap;
+use std::fmt;
 
 pub struct Day15;
 
 impl SolveAdvent for Day15 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let init_sequence = file_as_str.split(',');
-        let mut total_hash_value = 0;
-        for sequence in init_sequence {
-            total_hash_value += hash(sequence);
-        }
+        let total_hash_value = sum_of_hashes(&file_as_str);
         println!("Sum of hashes of each sequence step: {}", total_hash_value);
+        total_hash_value.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let init_sequence = file_as_str.split(',');
-
-        //Intialize the lens_map to contain all empty boxes from 0..255
-        let mut lens_map = HashMap::with_capacity(256);
-        for i in 0..256 {
-            lens_map.insert(i, Vec::new());
+        let mut lens_boxes = LensBoxes::new();
+        for step in parse_steps(&file_as_str) {
+            lens_boxes.apply(&step);
         }
+        let total_focusing_power = lens_boxes.total_focusing_power();
+        println!("Total Focusing Power: {}", total_focusing_power);
+        total_focusing_power.to_string()
+    }
+}
 
-        for step in init_sequence {
-            if step.contains('=') {
-                handle_equals_op(step, &mut lens_map);
-            } else if step.contains('-') {
-                handle_dash_op(step, &mut lens_map);
-            }
+pub fn trace(path_to_file: &str) {
+    //! Print the state of the boxes after every step, followed by the focusing power
+    //! of every lens, in the same format as the puzzle description.
+    let file_as_str = read_input_file(path_to_file);
+    let mut lens_boxes = LensBoxes::new();
+    for step in parse_steps(&file_as_str) {
+        lens_boxes.apply(&step);
+        println!("After \"{}\":\n{}", step, lens_boxes);
+    }
+    for (box_number, lens_box) in lens_boxes.boxes.iter().enumerate() {
+        for (slot_number, lens) in lens_box.lenses().enumerate() {
+            println!(
+                "{}: {} (box {}) * {} (slot {}) * {} (focal length) = {}",
+                lens.label,
+                box_number + 1,
+                box_number,
+                slot_number + 1,
+                slot_number + 1,
+                lens.focal_length,
+                lens_boxes.lens_focusing_power(&lens.label).unwrap()
+            );
         }
-
-        let mut total_focusing_power = 0;
-        for (box_number, lens_box) in lens_map {
-            for (slot_number, (_lens_label, lens_power)) in lens_box.into_iter().enumerate() {
-                total_focusing_power += (1 + box_number) * (slot_number + 1) * lens_power;
-            }
+        if lens_box.lens_count > 0 {
+            println!(
+                "Box {} focusing power: {}",
+                box_number,
+                lens_boxes.box_focusing_power(box_number)
+            );
         }
-        println!("Total Focusing Power: {}", total_focusing_power);
     }
+    println!(
+        "Total Focusing Power: {}",
+        lens_boxes.total_focusing_power()
+    );
+}
+
+fn sum_of_hashes(file_as_str: &str) -> usize {
+    //! Sum the hashes of every comma-separated step, ignoring the trailing
+    //! newline at the end of the input file.
+    file_as_str.trim().split(',').map(hash).sum()
 }
 
 fn hash(input: &str) -> usize {
@@ -54,40 +78,270 @@ fn hash(input: &str) -> usize {
     hash_value
 }
 
-fn handle_equals_op(step: &str, lens_map: &mut HashMap<usize, Vec<(String, usize)>>) {
-    //! Perform the changes to the lens_map as described for the equals operations.
-    let step = step.trim();
-    let mut step_splitter = step.split('=');
-    let label_to_insert = step_splitter.next().unwrap();
-    let focal_length_to_insert = step_splitter.next().unwrap().parse::<usize>().unwrap();
-    let specified_box = lens_map.get_mut(&hash(label_to_insert)).unwrap();
-
-    for (label, focal_size) in specified_box.iter_mut() {
-        if label == label_to_insert {
-            //As described in the directions, if the label is in the box,
-            //set the focal_size to the new focal_size to insert according to the step
-            *focal_size = focal_length_to_insert;
+///A single step of the initialization sequence.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    ///`label=focal_length`: put the lens in its box, replacing any lens with the same label.
+    Insert { label: String, focal_length: usize },
+    ///`label-`: take the lens with the label out of its box, if it is there.
+    Remove { label: String },
+}
+
+impl Step {
+    fn parse(step: &str) -> Step {
+        let step = step.trim();
+        if let Some((label, focal_length)) = step.split_once('=') {
+            return Step::Insert {
+                label: label.to_owned(),
+                focal_length: focal_length
+                    .parse::<usize>()
+                    .unwrap_or_else(|_| panic!("Invalid focal length in step {}", step)),
+            };
+        }
+        let label = step
+            .strip_suffix('-')
+            .unwrap_or_else(|| panic!("Step {} is neither an = nor a - operation", step));
+        Step::Remove {
+            label: label.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Insert {
+                label,
+                focal_length,
+            } => write!(f, "{}={}", label, focal_length),
+            Step::Remove { label } => write!(f, "{}-", label),
+        }
+    }
+}
+
+fn parse_steps(file_as_str: &str) -> Vec<Step> {
+    //! Split the comma separated initialization sequence into its steps.
+    file_as_str
+        .split(',')
+        .filter(|step| !step.trim().is_empty())
+        .map(Step::parse)
+        .collect::<Vec<_>>()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Lens {
+    label: String,
+    focal_length: usize,
+}
+
+///A single box, holding its lenses in the order they were inserted.
+#[derive(Debug, Clone, Default)]
+struct LensBox {
+    ///Removing a lens leaves a None in its slot, so that the indexes of the other
+    /// lenses do not change. The Nones are cleared out once there are too many of them.
+    slots: Vec<Option<Lens>>,
+    lens_count: usize,
+}
+
+impl LensBox {
+    fn lenses(&self) -> impl Iterator<Item = &Lens> {
+        //! The lenses in the box, from front to back.
+        self.slots.iter().flatten()
+    }
+
+    fn focusing_power(&self, box_number: usize) -> usize {
+        //! The sum of the focusing power of every lens in the box.
+        self.lenses()
+            .enumerate()
+            .map(|(slot_number, lens)| (box_number + 1) * (slot_number + 1) * lens.focal_length)
+            .sum()
+    }
+}
+
+///The 256 boxes that the HASHMAP procedure arranges the lenses into.
+#[derive(Debug, Clone)]
+struct LensBoxes {
+    boxes: Vec<LensBox>,
+    ///The index of each lens's slot in its box, so that a lens is found without searching.
+    /// The box number is the hash of the label.
+    slot_indexes: HashMap<String, usize>,
+}
+
+impl LensBoxes {
+    fn new() -> LensBoxes {
+        LensBoxes {
+            boxes: vec![LensBox::default(); 256],
+            slot_indexes: HashMap::new(),
+        }
+    }
+
+    fn apply(&mut self, step: &Step) {
+        match step {
+            Step::Insert {
+                label,
+                focal_length,
+            } => self.insert(label, *focal_length),
+            Step::Remove { label } => self.remove(label),
+        }
+    }
+
+    fn insert(&mut self, label: &str, focal_length: usize) {
+        //! As described in the directions, if the label is in the box, replace its focal length.
+        //! Otherwise, the lens goes at the back of the box.
+        let lens_box = &mut self.boxes[hash(label)];
+        if let Some(&slot_index) = self.slot_indexes.get(label) {
+            lens_box.slots[slot_index].as_mut().unwrap().focal_length = focal_length;
+            return;
+        }
+        self.slot_indexes
+            .insert(label.to_owned(), lens_box.slots.len());
+        lens_box.slots.push(Some(Lens {
+            label: label.to_owned(),
+            focal_length,
+        }));
+        lens_box.lens_count += 1;
+    }
+
+    fn remove(&mut self, label: &str) {
+        //! If the lens is in its box, remove it. The lenses behind it move forward.
+        let Some(slot_index) = self.slot_indexes.remove(label) else {
             return;
+        };
+        let lens_box = &mut self.boxes[hash(label)];
+        lens_box.slots[slot_index] = None;
+        lens_box.lens_count -= 1;
+        //Clear out the empty slots once they outnumber the lenses, which keeps this O(1) on average.
+        if lens_box.slots.len() > 2 * lens_box.lens_count + 8 {
+            lens_box.slots.retain(|slot| slot.is_some());
+            for (slot_index, lens) in lens_box.lenses().enumerate() {
+                *self.slot_indexes.get_mut(&lens.label).unwrap() = slot_index;
+            }
+        }
+    }
+
+    fn box_focusing_power(&self, box_number: usize) -> usize {
+        self.boxes[box_number].focusing_power(box_number)
+    }
+
+    fn lens_focusing_power(&self, label: &str) -> Option<usize> {
+        //! The focusing power of the lens with the label, or None if it is not in a box.
+        let slot_index = *self.slot_indexes.get(label)?;
+        let box_number = hash(label);
+        let lens_box = &self.boxes[box_number];
+        let slot_number = lens_box.slots[..slot_index].iter().flatten().count();
+        let focal_length = lens_box.slots[slot_index].as_ref()?.focal_length;
+        Some((box_number + 1) * (slot_number + 1) * focal_length)
+    }
+
+    fn total_focusing_power(&self) -> usize {
+        (0..self.boxes.len())
+            .map(|box_number| self.box_focusing_power(box_number))
+            .sum()
+    }
+}
+
+impl fmt::Display for LensBoxes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //! Every box that has a lens, such as `Box 0: [rn 1] [cm 2]`.
+        for (box_number, lens_box) in self.boxes.iter().enumerate() {
+            if lens_box.lens_count == 0 {
+                continue;
+            }
+            write!(f, "Box {}:", box_number)?;
+            for lens in lens_box.lenses() {
+                write!(f, " [{} {}]", lens.label, lens.focal_length)?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
     }
-    //If the lens is not in the box, then insert at the end of the box.
-    specified_box.push((label_to_insert.to_owned(), focal_length_to_insert));
 }
 
-fn handle_dash_op(step: &str, lens_map: &mut HashMap<usize, Vec<(String, usize)>>) {
-    //! Perform the changes to the lens_map as described for a dash operation.
-    let step = step.trim();
-    let label = step.replace('-', "");
-    let specified_box = lens_map.get_mut(&hash(&label)).unwrap();
-
-    let mut index = 0;
-    while index < specified_box.len() {
-        let (box_label, _) = specified_box.get(index).unwrap();
-        if box_label == &label {
-            //If the lens is in the box, remove it from the box.
-            specified_box.remove(index);
-            break;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7\n";
+
+    #[test]
+    fn test_sum_of_hashes_ignores_trailing_newline() {
+        assert_eq!(sum_of_hashes(EXAMPLE_INPUT), 1320);
+        assert_eq!(sum_of_hashes(EXAMPLE_INPUT.trim_end()), 1320);
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash("HASH"), 52);
+        let total = parse_steps(EXAMPLE_INPUT)
+            .iter()
+            .map(|step| hash(&step.to_string()))
+            .sum::<usize>();
+        assert_eq!(total, 1320);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut lens_boxes = LensBoxes::new();
+        let states = parse_steps(EXAMPLE_INPUT)
+            .iter()
+            .map(|step| {
+                lens_boxes.apply(step);
+                format!("After \"{}\":\n{}", step, lens_boxes)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(states[0], "After \"rn=1\":\nBox 0: [rn 1]\n");
+        assert_eq!(states[4], "After \"qp-\":\nBox 0: [rn 1] [cm 2]\n");
+        assert_eq!(
+            states[10],
+            "After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n"
+        );
+    }
+
+    #[test]
+    fn test_focusing_power() {
+        let mut lens_boxes = LensBoxes::new();
+        for step in parse_steps(EXAMPLE_INPUT) {
+            lens_boxes.apply(&step);
+        }
+        assert_eq!(lens_boxes.total_focusing_power(), 145);
+        assert_eq!(lens_boxes.box_focusing_power(0), 5);
+        assert_eq!(lens_boxes.box_focusing_power(3), 140);
+        assert_eq!(lens_boxes.lens_focusing_power("cm"), Some(4));
+        assert_eq!(lens_boxes.lens_focusing_power("ab"), Some(40));
+        assert_eq!(lens_boxes.lens_focusing_power("pc"), Some(72));
+        assert_eq!(lens_boxes.lens_focusing_power("qp"), None);
+    }
+
+    #[test]
+    fn test_many_removals() {
+        //Enough removals to clear out the empty slots, which must not move the remaining lenses.
+        let mut lens_boxes = LensBoxes::new();
+        let mut labels = Vec::new();
+        for a in 'a'..='z' {
+            for b in 'a'..='z' {
+                for c in 'a'..='z' {
+                    let label = format!("{}{}{}", a, b, c);
+                    if hash(&label) == 0 {
+                        labels.push(label);
+                    }
+                }
+            }
+        }
+        assert!(labels.len() > 20);
+        for label in labels.iter() {
+            lens_boxes.insert(label, 1);
+        }
+        for label in labels.iter().skip(1) {
+            lens_boxes.remove(label);
         }
-        index += 1;
+        assert!(lens_boxes.boxes[0].slots.len() < labels.len());
+        lens_boxes.insert("rn", 3);
+        lens_boxes.insert(&labels[0], 2);
+        assert_eq!(lens_boxes.lens_focusing_power(&labels[0]), Some(2));
+        assert_eq!(lens_boxes.lens_focusing_power("rn"), Some(6));
+        assert_eq!(
+            lens_boxes.to_string(),
+            format!("Box 0: [{} 2] [rn 3]\n", labels[0])
+        );
     }
 }
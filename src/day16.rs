@@ -5,7 +5,7 @@ use std::fmt;
 pub struct Day16;
 
 impl SolveAdvent for Day16 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let input_map = parse_input_into_map(&file_as_str);
         let starting_beam = LightBeam {
@@ -20,37 +20,39 @@ impl SolveAdvent for Day16 {
             "There are {} energized tiles for Part1",
             energized_tiles_count
         );
+        energized_tiles_count.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let input_map = parse_input_into_map(&file_as_str);
-        let mut max_energized_tiles_count = 0;
-        //For Part2, we iterator over all possible starting configuration to find
+        //For Part2, we consider all possible starting configurations to find
         //the configuration that generates the most energized tiles.
+        let mut starting_beams = Vec::new();
         let starting_beam_configurations = generate_unique_starting_configurations(&input_map);
         for (starting_direction, row_col_iterator) in starting_beam_configurations {
             for (row, column) in row_col_iterator {
-                let light_beam = LightBeam {
+                starting_beams.push(LightBeam {
                     row,
                     column,
                     map: &input_map,
                     direction: starting_direction.clone(),
-                };
-                let energized_tiles = find_energized_tiles(light_beam);
-                if energized_tiles > max_energized_tiles_count {
-                    max_energized_tiles_count = energized_tiles;
-                }
+                });
             }
         }
+        let max_energized_tiles_count = count_energized_tiles_for_each_start(&starting_beams)
+            .into_iter()
+            .max()
+            .unwrap_or(0);
         println!(
             "Maximum energized tiles in any starting configuration is {} for Part2",
             max_energized_tiles_count
         );
+        max_energized_tiles_count.to_string()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum Direction {
     Right,
     Left,
@@ -66,16 +68,37 @@ fn parse_input_into_map(file_as_str: &str) -> Vec<Vec<char>> {
 }
 
 impl Direction {
-    fn repr(&self) -> &'static str {
-        //! For hash/eq purposes, it is easier to just return
-        //! a string representation of the direction.
+    fn index(&self) -> usize {
+        //! A number for each direction, used to number the beam states.
+        match self {
+            Direction::Right => 0,
+            Direction::Left => 1,
+            Direction::Up => 2,
+            Direction::Down => 3,
+        }
+    }
+
+    fn arrow(&self) -> char {
+        //! The arrow used for the direction in the puzzle's illustrations.
         match self {
-            Direction::Down => "Down",
-            Direction::Up => "Up",
-            Direction::Right => "Right",
-            Direction::Left => "Left",
+            Direction::Down => 'v',
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Left => '<',
         }
     }
+
+    fn parse(direction: &str) -> Option<Direction> {
+        //! Accepts either the name of the direction or its arrow.
+        match direction.trim().to_lowercase().as_str() {
+            "right" | ">" => Some(Direction::Right),
+            "left" | "<" => Some(Direction::Left),
+            "up" | "^" => Some(Direction::Up),
+            "down" | "v" => Some(Direction::Down),
+            _ => None,
+        }
+    }
+
     fn forward_slash_reflection(&self) -> Self {
         //! Rotate the beam direction using the rules of a reflection
         //! against a forward slash.
@@ -106,7 +129,7 @@ struct LightBeam<'a> {
     column: usize,
     ///The direction the light beam is traveling
     direction: Direction,
-    map: &'a Vec<Vec<char>>,
+    map: &'a [Vec<char>],
 }
 
 impl<'a> fmt::Display for LightBeam<'a> {
@@ -223,12 +246,25 @@ impl<'a> LightBeam<'a> {
 fn find_energized_tiles(starting_beam: LightBeam) -> usize {
     //! Given a starting beam, returns the number of energized tiles
     //! for the given starting beam.
+    let beam_states = find_beam_states(starting_beam);
+    //The problem actually wants the number of unique locations reached, regardless
+    //of the direction of the beam at the time.
+    let unique_visited_locations = beam_states
+        .into_iter()
+        .map(|(row, column, _)| (row, column))
+        .collect::<HashSet<_>>();
+
+    unique_visited_locations.len()
+}
+
+fn find_beam_states(starting_beam: LightBeam) -> HashSet<(usize, usize, Direction)> {
+    //! Given a starting beam, returns every row, column, direction that a beam passes through.
     //Store a HashSet of row, column, direction to prevent infinite cycles, which are possible
-    let mut energized_sites = HashSet::new();
-    energized_sites.insert((
+    let mut beam_states = HashSet::new();
+    beam_states.insert((
         starting_beam.row,
         starting_beam.column,
-        Direction::Right.repr(),
+        starting_beam.direction.clone(),
     ));
     let mut lasers = vec![starting_beam];
     while !lasers.is_empty() {
@@ -236,30 +272,105 @@ fn find_energized_tiles(starting_beam: LightBeam) -> usize {
             .into_iter()
             .flat_map(|laser| laser.react_to_mirror())
             .filter(|laser_beam| {
-                if !energized_sites.contains(&(
+                beam_states.insert((
                     laser_beam.row,
                     laser_beam.column,
-                    laser_beam.direction.repr(),
-                )) {
-                    energized_sites.insert((
-                        laser_beam.row,
-                        laser_beam.column,
-                        laser_beam.direction.repr(),
-                    ));
-                    return true;
-                }
-                false
+                    laser_beam.direction.clone(),
+                ))
             })
             .collect::<Vec<_>>();
     }
-    //The problem actually wants the number of unique locations reached, regardless
-    //of the direction of the beam at the time.
-    let unique_visited_locations = energized_sites
-        .into_iter()
-        .map(|(row, column, _)| (row, column))
-        .collect::<HashSet<_>>();
+    beam_states
+}
 
-    unique_visited_locations.len()
+pub fn render(path_to_file: &str, start: Option<&str>, show_energized: bool) -> Result<(), String> {
+    //! Print the contraption as the puzzle illustrates it, for the beam starting at `start`
+    //! (such as `0,0,right`), which defaults to the top left corner moving right.
+    let file_as_str = read_input_file(path_to_file);
+    let input_map = parse_input_into_map(&file_as_str);
+    let starting_beam = match start {
+        Some(start) => parse_starting_beam(start, &input_map)?,
+        None => LightBeam {
+            row: 0,
+            column: 0,
+            direction: Direction::Right,
+            map: &input_map,
+        },
+    };
+    let beam_states = find_beam_states(starting_beam);
+    print!(
+        "{}",
+        render_beam_states(&input_map, &beam_states, show_energized)
+    );
+    Ok(())
+}
+
+fn parse_starting_beam<'a>(
+    start: &str,
+    input_map: &'a [Vec<char>],
+) -> Result<LightBeam<'a>, String> {
+    //! Parse a `row,column,direction` starting beam, which must be on the map.
+    let invalid_start = || {
+        format!(
+            "Expected a start of the form row,column,direction, got `{}`",
+            start
+        )
+    };
+    let [row, column, direction]: [&str; 3] = start
+        .split(',')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| invalid_start())?;
+    let row = row.trim().parse::<usize>().map_err(|_| invalid_start())?;
+    let column = column
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| invalid_start())?;
+    let direction = Direction::parse(direction).ok_or_else(invalid_start)?;
+    if row >= input_map.len() || column >= input_map[0].len() {
+        return Err(format!("The start {},{} is not on the map", row, column));
+    }
+    Ok(LightBeam {
+        row,
+        column,
+        direction,
+        map: input_map,
+    })
+}
+
+fn render_beam_states(
+    input_map: &[Vec<char>],
+    beam_states: &HashSet<(usize, usize, Direction)>,
+    show_energized: bool,
+) -> String {
+    //! Draw the contraption in the style of the puzzle's illustrations. If `show_energized`,
+    //! energized tiles are a `#` and every other tile a `.`. Otherwise, empty tiles a beam passes through
+    //! show the beam's direction, or how many beams pass through if there is more than one.
+    let mut rendered = String::new();
+    for (row, map_row) in input_map.iter().enumerate() {
+        for (column, symbol) in map_row.iter().enumerate() {
+            let directions = [
+                Direction::Right,
+                Direction::Left,
+                Direction::Up,
+                Direction::Down,
+            ]
+            .into_iter()
+            .filter(|direction| beam_states.contains(&(row, column, direction.clone())))
+            .collect::<Vec<_>>();
+            let tile = match (show_energized, symbol, directions.as_slice()) {
+                (true, _, []) => '.',
+                (true, _, _) => '#',
+                (false, '.', [direction]) => direction.arrow(),
+                (false, '.', []) => '.',
+                (false, '.', directions) => char::from_digit(directions.len() as u32, 10).unwrap(),
+                (false, symbol, _) => *symbol,
+            };
+            rendered.push(tile);
+        }
+        rendered.push('\n');
+    }
+    rendered
 }
 
 fn generate_unique_starting_configurations(
@@ -294,3 +405,341 @@ fn generate_unique_starting_configurations(
         ),
     ]
 }
+
+///Every state a beam can be in, which is a tile along with the direction the beam
+/// is traveling in it, and the states that each state leads to.
+struct BeamGraph {
+    columns: usize,
+    ///The states reached in one step from each state, indexed by state number.
+    successors: Vec<Vec<usize>>,
+}
+
+impl BeamGraph {
+    fn new(map: &[Vec<char>]) -> BeamGraph {
+        //! Build the graph by reacting a beam in every state to its mirror, exactly as
+        //! `find_energized_tiles` does.
+        let (rows, columns) = (map.len(), map[0].len());
+        let mut successors = vec![Vec::new(); rows * columns * 4];
+        for row in 0..rows {
+            for column in 0..columns {
+                for direction in [
+                    Direction::Right,
+                    Direction::Left,
+                    Direction::Up,
+                    Direction::Down,
+                ] {
+                    let state = (row * columns + column) * 4 + direction.index();
+                    let light_beam = LightBeam {
+                        row,
+                        column,
+                        direction,
+                        map,
+                    };
+                    successors[state] = light_beam
+                        .react_to_mirror()
+                        .into_iter()
+                        .map(|next_beam| {
+                            (next_beam.row * columns + next_beam.column) * 4
+                                + next_beam.direction.index()
+                        })
+                        .collect::<Vec<_>>();
+                }
+            }
+        }
+        BeamGraph {
+            columns,
+            successors,
+        }
+    }
+
+    fn state_of(&self, light_beam: &LightBeam) -> usize {
+        (light_beam.row * self.columns + light_beam.column) * 4 + light_beam.direction.index()
+    }
+}
+
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    //! Tarjan's algorithm, using an explicit stack as the graph is far too deep to recurse through.
+    //! A component is only finished once every component reachable from it is finished,
+    //! so the components are returned with the ones that lead nowhere else first.
+    let state_count = successors.len();
+    let mut index = vec![usize::MAX; state_count];
+    let mut lowlink = vec![0; state_count];
+    let mut on_stack = vec![false; state_count];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+    for root in 0..state_count {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        //Each entry is a state, and how many of its successors have been visited so far.
+        let mut call_stack = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some((state, successors_visited)) = call_stack.last_mut() {
+            let state = *state;
+            if let Some(&successor) = successors[state].get(*successors_visited) {
+                *successors_visited += 1;
+                if index[successor] == usize::MAX {
+                    index[successor] = next_index;
+                    lowlink[successor] = next_index;
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack[successor] = true;
+                    call_stack.push((successor, 0));
+                } else if on_stack[successor] {
+                    lowlink[state] = lowlink[state].min(index[successor]);
+                }
+                continue;
+            }
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[state]);
+            }
+            if lowlink[state] == index[state] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == state {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+fn count_energized_tiles_for_each_start(starting_beams: &[LightBeam]) -> Vec<usize> {
+    //! The number of energized tiles for each of the starting beams, which must all share a map.
+    //!
+    //! Every state in a strongly connected component of the beam graph reaches exactly the same states,
+    //! so the graph is condensed into its components. The tiles reached from a component are its own
+    //! tiles plus the tiles reached from the components it leads to, which are already known because
+    //! the components are processed with the ones that lead nowhere else first. Each set of tiles is
+    //! computed once, shared by every starting beam, and dropped once nothing else needs it.
+    let Some(first_beam) = starting_beams.first() else {
+        return Vec::new();
+    };
+    let map = first_beam.map;
+    let graph = BeamGraph::new(map);
+    let components = strongly_connected_components(&graph.successors);
+    let mut component_of = vec![0; graph.successors.len()];
+    for (component_number, component) in components.iter().enumerate() {
+        for &state in component {
+            component_of[state] = component_number;
+        }
+    }
+
+    //The condensed graph, and how many components lead to each component.
+    let mut component_successors = vec![Vec::new(); components.len()];
+    let mut remaining_predecessors = vec![0; components.len()];
+    for (component_number, component) in components.iter().enumerate() {
+        let mut next_components = component
+            .iter()
+            .flat_map(|&state| graph.successors[state].iter())
+            .map(|&successor| component_of[successor])
+            .filter(|&next_component| next_component != component_number)
+            .collect::<Vec<_>>();
+        next_components.sort_unstable();
+        next_components.dedup();
+        for &next_component in next_components.iter() {
+            remaining_predecessors[next_component] += 1;
+        }
+        component_successors[component_number] = next_components;
+    }
+    let mut starts_in_component = vec![Vec::new(); components.len()];
+    for (start_number, starting_beam) in starting_beams.iter().enumerate() {
+        starts_in_component[component_of[graph.state_of(starting_beam)]].push(start_number);
+    }
+
+    //The reached tiles are stored as a bitset with one bit per tile.
+    let tile_count = map.len() * map[0].len();
+    let mut reached_tiles: Vec<Option<Vec<u64>>> = vec![None; components.len()];
+    let mut energized_tile_counts = vec![0; starting_beams.len()];
+    for (component_number, component) in components.iter().enumerate() {
+        let mut tiles = vec![0_u64; tile_count.div_ceil(64)];
+        for &state in component {
+            let tile = state / 4;
+            tiles[tile / 64] |= 1 << (tile % 64);
+        }
+        for &next_component in component_successors[component_number].iter() {
+            let next_tiles = reached_tiles[next_component]
+                .as_ref()
+                .expect("Components must be processed after the components they lead to");
+            for (word, next_word) in tiles.iter_mut().zip(next_tiles) {
+                *word |= next_word;
+            }
+            remaining_predecessors[next_component] -= 1;
+            if remaining_predecessors[next_component] == 0 {
+                reached_tiles[next_component] = None;
+            }
+        }
+        let energized_tiles = tiles
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum::<usize>();
+        for &start_number in starts_in_component[component_number].iter() {
+            energized_tile_counts[start_number] = energized_tiles;
+        }
+        if remaining_predecessors[component_number] > 0 {
+            reached_tiles[component_number] = Some(tiles);
+        }
+    }
+    energized_tile_counts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    fn all_starting_beams(input_map: &[Vec<char>]) -> Vec<LightBeam<'_>> {
+        //! Every state a beam can be in, not only the ones on the edge of the map.
+        let mut starting_beams = Vec::new();
+        for row in 0..input_map.len() {
+            for column in 0..input_map[0].len() {
+                for direction in [
+                    Direction::Right,
+                    Direction::Left,
+                    Direction::Up,
+                    Direction::Down,
+                ] {
+                    starting_beams.push(LightBeam {
+                        row,
+                        column,
+                        direction,
+                        map: input_map,
+                    });
+                }
+            }
+        }
+        starting_beams
+    }
+
+    #[test]
+    fn test_render() {
+        let input_map = parse_input_into_map(EXAMPLE_INPUT);
+        let starting_beam = parse_starting_beam("0,0,right", &input_map).unwrap();
+        let beam_states = find_beam_states(starting_beam);
+        assert_eq!(
+            render_beam_states(&input_map, &beam_states, false),
+            r">|<<<\....
+|v-.\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\
+.v../2\\..
+<->-/vv|..
+.|<<<2-|.\
+.v//.|.v..
+"
+        );
+        assert_eq!(
+            render_beam_states(&input_map, &beam_states, true),
+            "######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..
+"
+        );
+        assert!(parse_starting_beam("9,9,^", &input_map).is_ok());
+        assert!(parse_starting_beam("10,0,up", &input_map).is_err());
+        assert!(parse_starting_beam("0,0,sideways", &input_map).is_err());
+        assert!(parse_starting_beam("0,0", &input_map).is_err());
+    }
+
+    fn check_against_simulation(input_map: &[Vec<char>]) {
+        let starting_beams = all_starting_beams(input_map);
+        let energized_tile_counts = count_energized_tiles_for_each_start(&starting_beams);
+        for (starting_beam, energized_tiles) in
+            starting_beams.into_iter().zip(energized_tile_counts)
+        {
+            let description = starting_beam.to_string();
+            assert_eq!(
+                find_energized_tiles(starting_beam),
+                energized_tiles,
+                "{}",
+                description
+            );
+        }
+    }
+
+    #[test]
+    fn test_example() {
+        let input_map = parse_input_into_map(EXAMPLE_INPUT);
+        let starting_beam = LightBeam {
+            row: 0,
+            column: 0,
+            direction: Direction::Right,
+            map: &input_map,
+        };
+        assert_eq!(find_energized_tiles(starting_beam.clone()), 46);
+        assert_eq!(
+            count_energized_tiles_for_each_start(&[starting_beam]),
+            vec![46]
+        );
+        let mut edge_beams = Vec::new();
+        for (direction, row_cols) in generate_unique_starting_configurations(&input_map) {
+            for (row, column) in row_cols {
+                edge_beams.push(LightBeam {
+                    row,
+                    column,
+                    direction: direction.clone(),
+                    map: &input_map,
+                });
+            }
+        }
+        assert_eq!(
+            count_energized_tiles_for_each_start(&edge_beams)
+                .into_iter()
+                .max(),
+            Some(51)
+        );
+        check_against_simulation(&input_map);
+    }
+
+    #[test]
+    fn test_generated_contraptions() {
+        //Pseudo-random contraptions, which have plenty of loops.
+        let mut seed = 12345_u64;
+        for size in [1, 2, 7, 25] {
+            let input_map = (0..size)
+                .map(|_| {
+                    (0..size)
+                        .map(|_| {
+                            seed = seed
+                                .wrapping_mul(6364136223846793005)
+                                .wrapping_add(1442695040888963407);
+                            ['.', '.', '.', '/', '\\', '-', '|'][(seed >> 33) as usize % 7]
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            check_against_simulation(&input_map);
+        }
+    }
+}
@@ -5,7 +5,7 @@ use std::time::Instant;
 pub struct Day17;
 
 impl SolveAdvent for Day17 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_contents = read_input_file(path_to_file);
         let number_maze = NumberMaze::new(file_contents);
         let now = Instant::now();
@@ -15,9 +15,10 @@ impl SolveAdvent for Day17 {
             "Best possible heat loss traversing the maze is: {}",
             best_possible_heat_losss
         );
+        best_possible_heat_losss.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_contents = read_input_file(path_to_file);
         let number_maze = NumberMaze::new(file_contents);
         let now = Instant::now();
@@ -27,6 +28,7 @@ impl SolveAdvent for Day17 {
             "Best possible heat loss traversing the maze is: {}",
             best_possible_heat_losss
         );
+        best_possible_heat_losss.to_string()
     }
 }
 
@@ -80,7 +82,7 @@ fn opposite_direction(dir: &char) -> char {
 }
 
 impl<'a> MazeProbe<'a> {
-    fn first_runner(maze: &NumberMaze) -> MazeProbe {
+    fn first_runner(maze: &NumberMaze) -> MazeProbe<'_> {
         //! Construct the first maze runner, which is located at
         //! position (0, 0) with a `uuid` of 0, new visited history.
         //! All future Runners through the maze should be spawned from this
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 pub struct Day19;
 
 impl SolveAdvent for Day19 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let (part_ratings, workflows) = process_into_workflows_ratings(file_as_str);
         let mut total_ratings_number = 0;
@@ -27,9 +27,10 @@ impl SolveAdvent for Day19 {
             "Total Accepted Part Ratings Numbers: {}",
             total_ratings_number
         );
+        total_ratings_number.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let (_, workflows) = process_into_workflows_ratings(file_as_str);
         //Walk all possible workflows
@@ -48,6 +49,7 @@ impl SolveAdvent for Day19 {
             "Total number of unique acceptable ratings: {}",
             total_acceptable_paths
         );
+        total_acceptable_paths.to_string()
     }
 }
 
@@ -179,7 +181,7 @@ fn traverse_workflows_recursively(
                 outer_vec.extend(next_nodes_response);
             }
         }
-        return outer_vec;
+        outer_vec
     }
 }
 
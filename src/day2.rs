@@ -1,128 +1,250 @@
 use crate::{read_input_file, SolveAdvent};
+use std::collections::HashMap;
+use std::fmt;
 
 pub struct Day2;
 
 impl SolveAdvent for Day2 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let mut sum_of_game_powers = 0;
-        for line in file_as_str.lines() {
-            sum_of_game_powers += possible_game_part1(line);
-        }
-        println!("Sum of possible game ids: {}", sum_of_game_powers);
+        let games = parse_games(&file_as_str).unwrap_or_else(|err| panic!("{}", err));
+        let sum_of_possible_games = possible_games(&games, &Bag::part1()).iter().sum::<u32>();
+        println!("Sum of possible game ids: {}", sum_of_possible_games);
+        sum_of_possible_games.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let mut sum_of_possible_games = 0;
-        for line in file_as_str.lines() {
-            sum_of_possible_games += min_cube_counts_game_part2(line);
-        }
-        println!("Sum of possible game ids: {}", sum_of_possible_games);
+        let games = parse_games(&file_as_str).unwrap_or_else(|err| panic!("{}", err));
+        let sum_of_game_powers = minimum_bags(&games)
+            .iter()
+            .map(|(_, minimum_bag)| minimum_bag.power(&Bag::part1()))
+            .sum::<u64>();
+        println!("Sum of game powers: {}", sum_of_game_powers);
+        sum_of_game_powers.to_string()
     }
 }
 
-fn possible_game_part1(line: &str) -> isize {
-    //! Returns the game_id of the game if it was possible,
-    //! otherwise returns 0 (which does not affect the sum).
-    let mut line_split_by_colon = line.split(':');
-    let game_number = line_split_by_colon
-        .next()
-        .unwrap()
-        .trim()
-        .replace("Game ", "")
-        .parse::<isize>()
-        .unwrap();
-    let reveals = line_split_by_colon.next().unwrap().split(';');
-
-    for reveal in reveals {
-        let reveal = reveal.trim();
-        let mut red_count = 12;
-        let mut green_count = 13;
-        let mut blue_count = 14;
-        let cubes_shown = reveal.trim().split(',').filter(|item| !item.is_empty());
-        for cube_shown in cubes_shown {
-            let mut cube_shown_split = cube_shown.trim().split(' ');
-            let cube_count = cube_shown_split
-                .next()
-                .unwrap()
-                .trim()
-                .parse::<isize>()
-                .unwrap();
-            let cube_color = cube_shown_split.next().unwrap().trim();
-            //Given the cube color, subtract the cube count from the numbers of each cube available.
-            if cube_color == "blue" {
-                blue_count -= cube_count;
-            } else if cube_color == "green" {
-                green_count -= cube_count;
-            } else if cube_color == "red" {
-                red_count -= cube_count;
+pub fn report_bag(path_to_file: &str, bag: &str, from_file: bool) -> Result<(), String> {
+    //! Check every game against a bag of cubes given as `12 red, 13 green, 14 blue`, or read
+    //! from the file `bag` when `from_file` is set. Any colours can be used.
+    let bag = if from_file {
+        Bag::parse(&read_input_file(bag))?
+    } else {
+        Bag::parse(bag)?
+    };
+    let games = parse_games(&read_input_file(path_to_file))?;
+    let possible_game_ids = possible_games(&games, &bag);
+    println!("With a bag of {}:", bag);
+    println!(
+        "{} games are possible, and their ids sum to {}",
+        possible_game_ids.len(),
+        possible_game_ids.iter().sum::<u32>()
+    );
+    for (id, minimum_bag) in minimum_bags(&games) {
+        println!(
+            "Game {}: {} at least, with a power of {}{}",
+            id,
+            minimum_bag,
+            minimum_bag.power(&bag),
+            if possible_game_ids.contains(&id) {
+                ""
             } else {
-                panic!("Recieved unexpected color: {}", cube_color);
+                " (impossible)"
             }
+        );
+    }
+    Ok(())
+}
+
+///The name of a cube colour, such as `red`.
+type Colour = String;
+
+///One game, with the cubes the elf showed in each reveal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game {
+    id: u32,
+    reveals: Vec<HashMap<Colour, u32>>,
+}
+
+///The number of cubes of each colour in a bag. Colours that are left out have no cubes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Bag {
+    cubes: HashMap<Colour, u32>,
+}
+
+impl Game {
+    fn from_line(line: &str) -> Result<Game, String> {
+        //! Parse a line such as `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+        let (game, reveals) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Expected `Game N: reveals`, got `{}`", line))?;
+        let id = game
+            .trim()
+            .strip_prefix("Game ")
+            .and_then(|id| id.trim().parse::<u32>().ok())
+            .ok_or_else(|| format!("Expected a game id, got `{}`", game))?;
+        let reveals = reveals
+            .split(';')
+            .map(parse_cubes)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Game { id, reveals })
+    }
+
+    fn is_possible_with(&self, bag: &Bag) -> bool {
+        //! A game is possible if no reveal showed more cubes of a colour than the bag holds.
+        self.reveals.iter().all(|reveal| {
+            reveal
+                .iter()
+                .all(|(colour, &count)| count <= bag.count(colour))
+        })
+    }
+
+    fn minimum_bag(&self) -> Bag {
+        //! The fewest cubes of each colour that the bag could have held for this game.
+        let mut minimum_bag = Bag::default();
+        for (colour, &count) in self.reveals.iter().flatten() {
+            let minimum_count = minimum_bag.cubes.entry(colour.clone()).or_insert(0);
+            *minimum_count = (*minimum_count).max(count);
         }
-        //If any of the counts becomes negative, then the elf showed you more cubes
-        //at once then were allowed, so the game is impossible.
-        if red_count < 0 || green_count < 0 || blue_count < 0 {
-            return 0;
-        }
+        minimum_bag
     }
-    game_number
 }
 
-fn min_cube_counts_game_part2(line: &str) -> isize {
-    //! Returns the game_id of the game if it was possible,
-    //! otherwise returns 0 (which does not affect the sum).
-    let mut line_split_by_colon = line.split(':');
-    let _game_number = line_split_by_colon
-        .next()
-        .unwrap()
-        .trim()
-        .replace("Game ", "")
-        .parse::<isize>()
+impl Bag {
+    fn part1() -> Bag {
+        //! The bag from the puzzle, with 12 red, 13 green and 14 blue cubes.
+        Bag::parse("12 red, 13 green, 14 blue").unwrap()
+    }
+
+    fn parse(bag: &str) -> Result<Bag, String> {
+        //! Parse cube counts separated by commas or new lines, such as `12 red, 13 green`.
+        let cubes = parse_cubes(&bag.replace('\n', ","))?;
+        Ok(Bag { cubes })
+    }
+
+    fn count(&self, colour: &str) -> u32 {
+        self.cubes.get(colour).copied().unwrap_or(0)
+    }
+
+    fn power(&self, bag: &Bag) -> u64 {
+        //! The number of cubes of each colour in `bag` multiplied together, counting from this bag.
+        //! A colour missing from this bag makes the power zero.
+        bag.cubes
+            .keys()
+            .map(|colour| self.count(colour) as u64)
+            .product()
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut colours = self.cubes.keys().collect::<Vec<_>>();
+        colours.sort();
+        let cubes = colours
+            .into_iter()
+            .map(|colour| format!("{} {}", self.cubes[colour], colour))
+            .collect::<Vec<_>>();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+fn parse_cubes(cubes: &str) -> Result<HashMap<Colour, u32>, String> {
+    //! Parse comma separated cube counts, such as `3 blue, 4 red`. A colour given more than once
+    //! has its counts added together.
+    let mut cube_counts = HashMap::new();
+    let cubes_shown = cubes
+        .split(',')
+        .map(|cube_shown| cube_shown.trim())
+        .filter(|cube_shown| !cube_shown.is_empty());
+    for cube_shown in cubes_shown {
+        let (cube_count, cube_colour) = cube_shown
+            .split_once(' ')
+            .ok_or_else(|| format!("Expected `count colour`, got `{}`", cube_shown))?;
+        let cube_count = cube_count
+            .parse::<u32>()
+            .map_err(|_| format!("Expected a cube count, got `{}`", cube_count))?;
+        *cube_counts
+            .entry(cube_colour.trim().to_owned())
+            .or_insert(0) += cube_count;
+    }
+    Ok(cube_counts)
+}
+
+fn parse_games(file_as_str: &str) -> Result<Vec<Game>, String> {
+    file_as_str
+        .lines()
+        .enumerate()
+        .map(|(line_number, line)| {
+            Game::from_line(line).map_err(|err| format!("Line {}: {}", line_number + 1, err))
+        })
+        .collect()
+}
+
+fn possible_games(games: &[Game], bag: &Bag) -> Vec<u32> {
+    //! The ids of the games that are possible with the bag.
+    games
+        .iter()
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| game.id)
+        .collect()
+}
+
+fn minimum_bags(games: &[Game]) -> Vec<(u32, Bag)> {
+    //! The id of every game, along with the smallest bag that makes it possible.
+    games
+        .iter()
+        .map(|game| (game.id, game.minimum_bag()))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_example() {
+        let games = parse_games(EXAMPLE_INPUT).unwrap();
+        assert_eq!(games[1].id, 2);
+        assert_eq!(games[1].reveals.len(), 3);
+        assert_eq!(games[1].reveals[1]["blue"], 4);
+        assert_eq!(possible_games(&games, &Bag::part1()), vec![1, 2, 5]);
+        let powers = minimum_bags(&games)
+            .iter()
+            .map(|(_, minimum_bag)| minimum_bag.power(&Bag::part1()))
+            .collect::<Vec<_>>();
+        assert_eq!(powers, vec![48, 12, 1560, 630, 36]);
+        assert_eq!(
+            minimum_bags(&games)[0].1.to_string(),
+            "6 blue, 2 green, 4 red"
+        );
+    }
+
+    #[test]
+    fn test_any_colours() {
+        let games = parse_games(
+            "Game 7: 2 sky blue, 1 red; 4 gold
+Game 9: 1 red; 2 red",
+        )
         .unwrap();
-    let reveals = line_split_by_colon.next().unwrap().split(';');
-    let mut max_red_count_required = 0;
-    let mut max_green_count_required = 0;
-    let mut max_blue_count_required = 0;
-
-    for reveal in reveals {
-        let reveal = reveal.trim();
-        let mut required_red_count = 0;
-        let mut required_green_count = 0;
-        let mut required_blue_count = 0;
-        let cubes_shown = reveal.trim().split(',').filter(|item| !item.is_empty());
-        for cube_shown in cubes_shown {
-            let mut cube_shown_split = cube_shown.trim().split(' ');
-            let cube_count = cube_shown_split
-                .next()
-                .unwrap()
-                .trim()
-                .parse::<isize>()
-                .unwrap();
-            let cube_color = cube_shown_split.next().unwrap().trim();
-            //Given the cube color, subtract the cube count from the numbers of each cube available.
-            if cube_color == "blue" {
-                required_blue_count += cube_count;
-            } else if cube_color == "green" {
-                required_green_count += cube_count;
-            } else if cube_color == "red" {
-                required_red_count += cube_count;
-            } else {
-                panic!("Recieved unexpected color: {}", cube_color);
-            }
-        }
-        //For each reveal round, if any required cube count is larger than the max_required across
-        //all the reveals for that cube color, set the max to the new required count.
-        if required_red_count > max_red_count_required {
-            max_red_count_required = required_red_count;
-        }
-        if required_blue_count > max_blue_count_required {
-            max_blue_count_required = required_blue_count;
-        }
-        if required_green_count > max_green_count_required {
-            max_green_count_required = required_green_count;
-        }
+        let bag = Bag::parse("3 sky blue\n4 gold, 1 red").unwrap();
+        assert_eq!(bag.count("sky blue"), 3);
+        assert_eq!(possible_games(&games, &bag), vec![7]);
+        assert_eq!(possible_games(&games, &Bag::part1()), vec![9]);
+        assert_eq!(games[0].minimum_bag().power(&bag), 8);
+        //Game 9 never shows gold, so its power is zero.
+        assert_eq!(games[1].minimum_bag().power(&bag), 0);
+        assert!(Game::from_line("Game 1 3 blue").is_err());
+        assert!(Game::from_line("Game x: 3 blue").is_err());
+        assert!(Game::from_line("Game 1: blue").is_err());
+        assert!(parse_games("Game 1: 1 red\nGame 2: many red")
+            .unwrap_err()
+            .starts_with("Line 2"));
     }
-    max_red_count_required * max_blue_count_required * max_green_count_required
 }
@@ -1,38 +1,219 @@
 use crate::{read_input_file, SolveAdvent};
 use std::collections::{HashMap, VecDeque};
+use std::fmt;
 
 pub struct Day20;
 
 impl SolveAdvent for Day20 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let input_file = read_input_file(path_to_file);
-        let pulse_modules = PulseModule::from_input_file(&input_file);
-        let mut module_map = pulse_modules
-            .into_iter()
-            .map(|module| (module.name.clone(), module))
-            .collect::<HashMap<_, _>>();
-        let mut total_low_pulses: usize = 0;
-        let mut total_high_pulses: usize = 0;
         let total_button_presses = 1000;
-        for _button_press in 0..total_button_presses {
-            let (low_pulse_count, high_pulse_count) = invoke_pulse_loop_via_button(&mut module_map);
-            total_high_pulses += high_pulse_count;
-            total_low_pulses += low_pulse_count;
+        let pulse_totals = count_pulses(
+            &input_file,
+            &ModuleRegistry::default(),
+            total_button_presses,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+        pulse_totals.print(total_button_presses);
+        pulse_totals.product().unwrap().to_string()
+    }
+
+    fn solve_part2(path_to_file: &str) -> String {
+        let _ = path_to_file;
+        String::new()
+    }
+}
+
+pub fn report_presses(path_to_file: &str, total_button_presses: u64) -> Result<String, String> {
+    //! Count the pulses sent over any number of button presses, which can be far too many to simulate.
+    let input_file = read_input_file(path_to_file);
+    let pulse_totals = count_pulses(
+        &input_file,
+        &ModuleRegistry::default(),
+        total_button_presses,
+    )?;
+    pulse_totals.print(total_button_presses);
+    pulse_totals
+        .product()
+        .map(|product| product.to_string())
+        .ok_or_else(|| "The product of the pulse counts overflows".to_owned())
+}
+
+///The most presses that are simulated while looking for a repeated network state. Networks built
+/// from long counters, like the puzzle inputs, may not repeat for around 10^14 presses, far beyond this.
+const MAX_SIMULATED_PRESSES: u64 = 1_000_000;
+
+///The pulses sent over a number of button presses.
+#[derive(Debug, Clone, PartialEq)]
+struct PulseTotals {
+    low_pulses: u128,
+    high_pulses: u128,
+    ///The number of presses before the network first entered its cycle of states, and the length
+    /// of the cycle, if one was found before all of the presses were simulated.
+    cycle: Option<(u64, u64)>,
+}
+
+impl PulseTotals {
+    fn print(&self, total_button_presses: u64) {
+        if let Some((cycle_start, cycle_length)) = self.cycle {
+            println!("After {cycle_start} button presses, the network repeats its state every {cycle_length} presses");
         }
-        println!("After {total_button_presses} button presses, there were {total_low_pulses} low pulses and {total_high_pulses} high pulses");
         println!(
-            "Multiplied low & high pulse counts together gives {}",
-            total_low_pulses * total_high_pulses
+            "After {total_button_presses} button presses, there were {} low pulses and {} high pulses",
+            self.low_pulses, self.high_pulses
         );
+        if let Some(product) = self.product() {
+            println!(
+                "Multiplied low & high pulse counts together gives {}",
+                product
+            );
+        }
     }
 
-    fn solve_part2(path_to_file: &str) {
-        let _ = path_to_file;
+    fn add(&mut self, (low_pulse_count, high_pulse_count): (usize, usize)) {
+        self.low_pulses += low_pulse_count as u128;
+        self.high_pulses += high_pulse_count as u128;
+    }
+
+    fn product(&self) -> Option<u128> {
+        //! The low pulse count multiplied by the high pulse count, or None if it is too large.
+        self.low_pulses.checked_mul(self.high_pulses)
+    }
+}
+
+fn count_pulses(
+    input_file: &str,
+    registry: &ModuleRegistry,
+    total_button_presses: u64,
+) -> Result<PulseTotals, String> {
+    //! Press the button `total_button_presses` times, counting the low and high pulses sent.
+    //! Once the state of the whole network repeats, every press after it follows the same cycle,
+    //! so the rest of the presses are counted from the cycle instead of being simulated.
+    //! The cycle is found with Brent's algorithm, which only keeps one earlier state at a time,
+    //! and the networks are then rebuilt to find where the cycle starts.
+    let mut network = Network::new(input_file, registry)?;
+    let mut pulse_totals = PulseTotals {
+        low_pulses: 0,
+        high_pulses: 0,
+        cycle: None,
+    };
+    let mut presses_done = 0;
+    let mut tortoise_state = network.state();
+    let mut tortoise_presses = 0;
+    let mut power = 1;
+    let cycle_length = loop {
+        if presses_done == total_button_presses {
+            return Ok(pulse_totals);
+        }
+        if presses_done == MAX_SIMULATED_PRESSES {
+            return Err(format!(
+                "The network did not repeat a state within {} button presses, so only up to that many presses can be counted",
+                MAX_SIMULATED_PRESSES
+            ));
+        }
+        pulse_totals.add(network.press());
+        presses_done += 1;
+        let state = network.state();
+        if state == tortoise_state {
+            break presses_done - tortoise_presses;
+        }
+        if presses_done - tortoise_presses == power {
+            tortoise_state = state;
+            tortoise_presses = presses_done;
+            power *= 2;
+        }
+    };
+
+    //With one network a cycle ahead of the other, they first match at the start of the cycle.
+    let mut network = Network::new(input_file, registry)?;
+    let mut cycle_ahead = Network::new(input_file, registry)?;
+    let mut cycle_ahead_totals = pulse_totals.clone();
+    cycle_ahead_totals.low_pulses = 0;
+    cycle_ahead_totals.high_pulses = 0;
+    for _ in 0..cycle_length {
+        cycle_ahead_totals.add(cycle_ahead.press());
+    }
+    let mut start_totals = PulseTotals {
+        low_pulses: 0,
+        high_pulses: 0,
+        cycle: None,
+    };
+    let mut cycle_start = 0;
+    while network.state() != cycle_ahead.state() {
+        start_totals.add(network.press());
+        cycle_ahead_totals.add(cycle_ahead.press());
+        cycle_start += 1;
+    }
+    let cycle_low = cycle_ahead_totals.low_pulses - start_totals.low_pulses;
+    let cycle_high = cycle_ahead_totals.high_pulses - start_totals.high_pulses;
+
+    let remaining_presses = total_button_presses - cycle_start;
+    let full_cycles = (remaining_presses / cycle_length) as u128;
+    let mut leftover_totals = start_totals.clone();
+    for _ in 0..remaining_presses % cycle_length {
+        leftover_totals.add(network.press());
+    }
+    Ok(PulseTotals {
+        low_pulses: leftover_totals.low_pulses + full_cycles * cycle_low,
+        high_pulses: leftover_totals.high_pulses + full_cycles * cycle_high,
+        cycle: Some((cycle_start, cycle_length)),
+    })
+}
+
+///A whole network of modules, which can be pressed and compared.
+struct Network {
+    module_map: HashMap<String, PulseModule>,
+    ///Every module name, sorted, so that states are recorded in the same order.
+    module_names: Vec<String>,
+}
+
+impl Network {
+    fn new(input_file: &str, registry: &ModuleRegistry) -> Result<Network, String> {
+        let module_map = PulseModule::from_input_file(input_file, registry)?
+            .into_iter()
+            .map(|module| (module.name.clone(), module))
+            .collect::<HashMap<_, _>>();
+        let mut module_names = module_map.keys().cloned().collect::<Vec<_>>();
+        module_names.sort();
+        Ok(Network {
+            module_map,
+            module_names,
+        })
+    }
+
+    fn press(&mut self) -> (usize, usize) {
+        invoke_pulse_loop_via_button(&mut self.module_map)
+    }
+
+    fn state(&self) -> Vec<u64> {
+        network_state(&self.module_map, &self.module_names)
     }
 }
 
+fn network_state(module_map: &HashMap<String, PulseModule>, module_names: &[String]) -> Vec<u64> {
+    //! Pack the state of every module into bits, such as the state of every flip-flop and the memory
+    //! of every conjunction, in the order of `module_names` so that equal states match.
+    let mut state = Vec::new();
+    let mut bit_count = 0;
+    let mut push_bit = |bit: bool| {
+        if bit_count % 64 == 0 {
+            state.push(0);
+        }
+        if bit {
+            *state.last_mut().unwrap() |= 1 << (bit_count % 64);
+        }
+        bit_count += 1;
+    };
+    for module_name in module_names {
+        module_map[module_name]
+            .behaviour
+            .record_state(&mut push_bit);
+    }
+    state
+}
+
 ///A pulses are either High or Low.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum PulseType {
     High,
     Low,
@@ -81,33 +262,267 @@ struct PulseMessage {
     recipients: Vec<String>,
 }
 
-///The 3 types of modules in the problem,
-/// disregarding the button to initiate the whole
-/// chain.
+///How a kind of module reacts to the pulses it receives. Each module owns its own
+/// behaviour, so any state it keeps is private to that module.
+trait PulseBehaviour: fmt::Debug {
+    ///Called once the whole network is built, with the names of every module that sends to this one.
+    fn connect_inputs(&mut self, _input_names: &[String]) {}
+
+    ///React to a pulse from `sender`, returning the pulse to send to every destination, if any.
+    fn react(&mut self, sender: &str, pulse: PulseType) -> Option<PulseType>;
+
+    ///Pass each bit of the module's state to `push_bit`, in the same order every time.
+    /// Modules without any state record nothing.
+    fn record_state(&self, _push_bit: &mut dyn FnMut(bool)) {}
+}
+
+///Broadcasters do not to maintain any extra state, and pass on every pulse they receive.
 #[derive(Debug, Clone)]
-enum PulseModuleKind {
-    ///FlipFlops maintain a state of either High or Low. This affect whether
-    /// they send out a low or high pulse upon receiving a message.
-    FlipFlop { state: PulseType },
-    ///Conjunctions maintain a memory of the last input of each input
-    /// module. `pulse_memory` is a map of the Input Module Name to the last input
-    /// from that input module.
-    Conjunction {
-        pulse_memory: HashMap<String, PulseType>,
-    },
-    ///Broadcasters do not to maintain any extra state.
-    Broadcaster,
+struct Broadcaster;
+
+impl PulseBehaviour for Broadcaster {
+    fn react(&mut self, _sender: &str, pulse: PulseType) -> Option<PulseType> {
+        Some(pulse)
+    }
 }
 
-///Represents a single pulse module.
+///FlipFlops maintain a state of either High or Low. This affect whether
+/// they send out a low or high pulse upon receiving a message.
 #[derive(Debug, Clone)]
+struct FlipFlop {
+    state: PulseType,
+}
+
+impl PulseBehaviour for FlipFlop {
+    fn react(&mut self, _sender: &str, pulse: PulseType) -> Option<PulseType> {
+        //If a high pulse is received, do nothing.
+        if let PulseType::High = pulse {
+            return None;
+        }
+        //Otherwise, flip the state and send out the new state.
+        match self.state {
+            PulseType::High => self.state = PulseType::Low,
+            PulseType::Low => self.state = PulseType::High,
+        };
+        Some(self.state)
+    }
+
+    fn record_state(&self, push_bit: &mut dyn FnMut(bool)) {
+        push_bit(self.state == PulseType::High);
+    }
+}
+
+///Conjunctions maintain a memory of the last input of each input module.
+#[derive(Debug, Clone, Default)]
+struct Conjunction {
+    ///The name of each input module and the last pulse from it, sorted by name.
+    pulse_memory: Vec<(String, PulseType)>,
+}
+
+impl PulseBehaviour for Conjunction {
+    fn connect_inputs(&mut self, input_names: &[String]) {
+        //Every input starts out remembering a Low pulse.
+        let mut input_names = input_names.to_vec();
+        input_names.sort();
+        self.pulse_memory = input_names
+            .into_iter()
+            .map(|input_name| (input_name, PulseType::Low))
+            .collect::<Vec<_>>();
+    }
+
+    fn react(&mut self, sender: &str, pulse: PulseType) -> Option<PulseType> {
+        //The conjunction module first updates its memory state of the input to the new input.
+        if let Some((_, memory_state)) = self
+            .pulse_memory
+            .iter_mut()
+            .find(|(input_name, _)| input_name == sender)
+        {
+            *memory_state = pulse;
+        }
+        //If all of the inputs in the conjunction history are high, then a low pulse
+        //is sent out, otherwise a high pulse.
+        let inputs_all_high = self
+            .pulse_memory
+            .iter()
+            .all(|(_, memory_state)| memory_state == &PulseType::High);
+        if inputs_all_high {
+            Some(PulseType::Low)
+        } else {
+            Some(PulseType::High)
+        }
+    }
+
+    fn record_state(&self, push_bit: &mut dyn FnMut(bool)) {
+        for (_, memory_state) in self.pulse_memory.iter() {
+            push_bit(memory_state == &PulseType::High);
+        }
+    }
+}
+
+///Inverters send the opposite of every pulse they receive.
+#[derive(Debug, Clone)]
+struct Inverter;
+
+impl PulseBehaviour for Inverter {
+    fn react(&mut self, _sender: &str, pulse: PulseType) -> Option<PulseType> {
+        match pulse {
+            PulseType::High => Some(PulseType::Low),
+            PulseType::Low => Some(PulseType::High),
+        }
+    }
+}
+
+///Delay lines send the pulse they received the time before, starting with a Low pulse,
+/// and keep the pulse just received until the next one arrives.
+#[derive(Debug, Clone)]
+struct DelayLine {
+    held_pulse: PulseType,
+}
+
+impl PulseBehaviour for DelayLine {
+    fn react(&mut self, _sender: &str, pulse: PulseType) -> Option<PulseType> {
+        Some(std::mem::replace(&mut self.held_pulse, pulse))
+    }
+
+    fn record_state(&self, push_bit: &mut dyn FnMut(bool)) {
+        push_bit(self.held_pulse == PulseType::High);
+    }
+}
+
+///Counters count the low pulses they receive, and ignore high pulses like a flip-flop.
+/// Every `period`th low pulse sends a high pulse, and every other low pulse sends a low pulse.
+#[derive(Debug, Clone)]
+struct Counter {
+    count: usize,
+    period: usize,
+}
+
+impl Counter {
+    fn new(period: usize) -> Counter {
+        assert!(period > 0, "A counter needs a period of at least 1");
+        Counter { count: 0, period }
+    }
+}
+
+impl PulseBehaviour for Counter {
+    fn react(&mut self, _sender: &str, pulse: PulseType) -> Option<PulseType> {
+        if let PulseType::High = pulse {
+            return None;
+        }
+        self.count = (self.count + 1) % self.period;
+        if self.count == 0 {
+            Some(PulseType::High)
+        } else {
+            Some(PulseType::Low)
+        }
+    }
+
+    fn record_state(&self, push_bit: &mut dyn FnMut(bool)) {
+        let bit_count = usize::BITS - (self.period - 1).leading_zeros();
+        for bit in 0..bit_count {
+            push_bit(self.count >> bit & 1 == 1);
+        }
+    }
+}
+
+type ModuleConstructor = Box<dyn Fn() -> Box<dyn PulseBehaviour>>;
+
+///The kinds of module that can appear in an input file, found by the prefix before
+/// the module's name, such as `%` for flip-flops.
+struct ModuleRegistry {
+    ///Each prefix and the constructor for its kind of module. When several prefixes match
+    /// a module, the longest one wins.
+    constructors: Vec<(String, ModuleConstructor)>,
+}
+
+impl Default for ModuleRegistry {
+    fn default() -> ModuleRegistry {
+        //! The flip-flops (`%`) and conjunctions (`&`) of the puzzle, along with inverters (`!`),
+        //! delay lines (`~`) and decade counters (`#`).
+        let mut registry = ModuleRegistry::new();
+        //The prefixes are all different, so none of these can fail.
+        registry
+            .register("%", || {
+                Box::new(FlipFlop {
+                    state: PulseType::Low,
+                })
+            })
+            .unwrap();
+        registry
+            .register("&", || Box::new(Conjunction::default()))
+            .unwrap();
+        registry.register("!", || Box::new(Inverter)).unwrap();
+        registry
+            .register("~", || {
+                Box::new(DelayLine {
+                    held_pulse: PulseType::Low,
+                })
+            })
+            .unwrap();
+        registry
+            .register("#", || Box::new(Counter::new(10)))
+            .unwrap();
+        registry
+    }
+}
+
+impl ModuleRegistry {
+    fn new() -> ModuleRegistry {
+        //! A registry without any kinds of module. The broadcaster is always known.
+        ModuleRegistry {
+            constructors: Vec::new(),
+        }
+    }
+
+    fn register(
+        &mut self,
+        prefix: &str,
+        constructor: impl Fn() -> Box<dyn PulseBehaviour> + 'static,
+    ) -> Result<(), String> {
+        //! Add a kind of module. Each prefix can only be registered once.
+        if self
+            .constructors
+            .iter()
+            .any(|(registered_prefix, _)| registered_prefix == prefix)
+        {
+            return Err(format!("The prefix `{}` is already registered", prefix));
+        }
+        self.constructors
+            .push((prefix.to_owned(), Box::new(constructor)));
+        Ok(())
+    }
+
+    fn build(&self, module_name: &str) -> Result<(String, Box<dyn PulseBehaviour>), String> {
+        //! Split the prefix off of the module name, and build the behaviour for its kind.
+        if module_name == "broadcaster" {
+            return Ok((module_name.to_owned(), Box::new(Broadcaster)));
+        }
+        self.constructors
+            .iter()
+            .filter_map(|(prefix, constructor)| {
+                let name = module_name.strip_prefix(prefix.as_str())?;
+                Some((prefix.len(), name, constructor))
+            })
+            .max_by_key(|&(prefix_length, _, _)| prefix_length)
+            .map(|(_, name, constructor)| (name.to_owned(), constructor()))
+            .ok_or_else(|| {
+                format!(
+                    "Module `{}` does not start with a known prefix",
+                    module_name
+                )
+            })
+    }
+}
+
+///Represents a single pulse module.
+#[derive(Debug)]
 struct PulseModule {
     ///The name of the module, for example `a`
     name: String,
     ///The names of the modules this module sends messages to
     destination_modules: Vec<String>,
-    ///The type of module this is.
-    kind: PulseModuleKind,
+    ///How the module reacts to pulses, which depends on the kind of module.
+    behaviour: Box<dyn PulseBehaviour>,
 }
 
 impl PulseModule {
@@ -116,123 +531,309 @@ impl PulseModule {
         if !message.recipients.contains(&self.name) {
             return None;
         }
-        match &mut self.kind {
-            PulseModuleKind::Broadcaster => {
-                //The broadcaster module passes the input pulse value to
-                //every module in its destination module list.
-                Some(PulseMessage {
-                    sender: self.name.clone(),
-                    state: message.state,
-                    recipients: self.destination_modules.clone(),
-                })
-            }
-            PulseModuleKind::Conjunction { pulse_memory } => {
-                //The conjunction module first updates its memory state of the input to the new input.
-                if let Some(memory_state) = pulse_memory.get_mut(&message.sender) {
-                    *memory_state = message.state;
-                }
-                let inputs_all_high = pulse_memory.values().all(|memory_state| {
-                    if let PulseType::High = memory_state {
-                        return true;
-                    }
-                    false
-                });
-                //If all of the inputs in the conjunction history are high, then a low pulse
-                //is sent out, otherwise a low pulse.
-                let pulse_type_to_send = if inputs_all_high {
-                    PulseType::Low
-                } else {
-                    PulseType::High
-                };
-                Some(PulseMessage {
-                    sender: self.name.clone(),
-                    state: pulse_type_to_send,
-                    recipients: self.destination_modules.clone(),
-                })
-            }
-            PulseModuleKind::FlipFlop { state } => {
-                //If a high pulse is received, do nothing.
-                if let PulseType::High = message.state {
-                    return None;
-                }
-                //Otherwise, flip the state and send out the new state.
-                match state {
-                    PulseType::High => *state = PulseType::Low,
-                    PulseType::Low => *state = PulseType::High,
-                };
-                Some(PulseMessage {
-                    sender: self.name.clone(),
-                    state: *state,
-                    recipients: self.destination_modules.clone(),
-                })
-            }
-        }
+        let pulse_type_to_send = self.behaviour.react(&message.sender, message.state)?;
+        Some(PulseMessage {
+            sender: self.name.clone(),
+            state: pulse_type_to_send,
+            recipients: self.destination_modules.clone(),
+        })
     }
-    fn from_input_file(input_file: &str) -> Vec<PulseModule> {
+    fn from_input_file(
+        input_file: &str,
+        registry: &ModuleRegistry,
+    ) -> Result<Vec<PulseModule>, String> {
         //! Build out the `PulseModules` from the input file.
         let mut pulse_modules = input_file
             .lines()
-            .map(PulseModule::from_line)
-            .collect::<Vec<_>>();
-        //At this point, the problem is that Conjunction modules have not had their `pulse_memory`
-        //map filled to contain all of the inputs to said conjunction module intialized to a Low Pulse.
-        let mut destination_map = HashMap::new();
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| PulseModule::from_line(line, registry))
+            .collect::<Result<Vec<_>, _>>()?;
+        //At this point, modules such as conjunctions do not know their inputs, which
+        //are the modules that list them as a destination.
+        let mut input_map = HashMap::<String, Vec<String>>::new();
         for pulse_module in pulse_modules.iter() {
-            destination_map.insert(
-                pulse_module.name.clone(),
-                pulse_module.destination_modules.clone(),
-            );
+            for destination in pulse_module.destination_modules.iter() {
+                input_map
+                    .entry(destination.clone())
+                    .or_default()
+                    .push(pulse_module.name.clone());
+            }
         }
-
         for pulse_module in pulse_modules.iter_mut() {
-            if let PulseModuleKind::Conjunction { pulse_memory } = &mut pulse_module.kind {
-                for (module_name, module_dests) in destination_map.iter() {
-                    //For every conjunction module, any modules that list this conjunction module in their destination
-                    //list should have the input module's name added to the conjunction modules pulse_memory map initialized to
-                    //a Low pulse.
-                    if module_dests.contains(&pulse_module.name) {
-                        pulse_memory.insert(module_name.clone(), PulseType::Low);
-                    }
-                }
-            }
+            let input_names = input_map.remove(&pulse_module.name).unwrap_or_default();
+            pulse_module.behaviour.connect_inputs(&input_names);
         }
-        pulse_modules
+        Ok(pulse_modules)
     }
-    fn from_line(line: &str) -> PulseModule {
-        //! Construct a `PulseModule` from a line in the input file. The initialization
-        //! for Conjunction modules in incomplete, as explained in the `from_input_file` method.
-        let mut line_split = line.split("->");
-        let module_name = line_split.next().unwrap().trim();
-        let destination_modules = line_split
-            .next()
-            .unwrap()
+    fn from_line(line: &str, registry: &ModuleRegistry) -> Result<PulseModule, String> {
+        //! Construct a `PulseModule` from a line in the input file. The inputs of the
+        //! module are connected in the `from_input_file` method.
+        let (module_name, destinations) = line
+            .split_once("->")
+            .ok_or_else(|| format!("Module line `{}` has no `->`", line))?;
+        let destination_modules = destinations
             .trim()
             .replace(' ', "")
             .split(',')
             .map(|segment| segment.to_owned())
             .collect::<Vec<_>>();
-        if module_name == "broadcaster" {
-            PulseModule {
-                name: module_name.to_owned(),
-                destination_modules,
-                kind: PulseModuleKind::Broadcaster,
-            }
-        } else if module_name.starts_with('%') {
-            PulseModule {
-                name: module_name.replace('%', ""),
-                destination_modules,
-                kind: PulseModuleKind::FlipFlop {
-                    state: PulseType::Low,
-                },
-            }
-        } else {
-            PulseModule {
-                name: module_name.replace('&', ""),
-                destination_modules,
-                kind: PulseModuleKind::Conjunction {
-                    pulse_memory: HashMap::new(),
-                },
+        let (name, behaviour) = registry.build(module_name.trim())?;
+        Ok(PulseModule {
+            name,
+            destination_modules,
+            behaviour,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FIRST_EXAMPLE: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const SECOND_EXAMPLE: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    const MIXED_EXAMPLE: &str = "broadcaster -> a, d
+%a -> n, con
+!n -> con, c
+~d -> con, d2
+~d2 -> a
+#c -> con
+&con -> output";
+
+    fn simulate_every_press(input_file: &str, total_button_presses: u64) -> (u128, u128) {
+        let mut module_map = PulseModule::from_input_file(input_file, &ModuleRegistry::default())
+            .unwrap()
+            .into_iter()
+            .map(|module| (module.name.clone(), module))
+            .collect::<HashMap<_, _>>();
+        let mut totals = (0, 0);
+        for _ in 0..total_button_presses {
+            let (low_pulse_count, high_pulse_count) = invoke_pulse_loop_via_button(&mut module_map);
+            totals.0 += low_pulse_count as u128;
+            totals.1 += high_pulse_count as u128;
+        }
+        totals
+    }
+
+    #[test]
+    fn test_examples() {
+        let pulse_totals = count_pulses(FIRST_EXAMPLE, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(
+            (pulse_totals.low_pulses, pulse_totals.high_pulses),
+            (8000, 4000)
+        );
+        assert_eq!(pulse_totals.cycle, Some((0, 1)));
+        let pulse_totals = count_pulses(SECOND_EXAMPLE, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(
+            (pulse_totals.low_pulses, pulse_totals.high_pulses),
+            (4250, 2750)
+        );
+        assert_eq!(pulse_totals.cycle, Some((0, 4)));
+    }
+
+    #[test]
+    fn test_extrapolation_matches_simulation() {
+        for input_file in [FIRST_EXAMPLE, SECOND_EXAMPLE, MIXED_EXAMPLE] {
+            for total_button_presses in 0..30 {
+                let pulse_totals =
+                    count_pulses(input_file, &ModuleRegistry::default(), total_button_presses)
+                        .unwrap();
+                assert_eq!(
+                    (pulse_totals.low_pulses, pulse_totals.high_pulses),
+                    simulate_every_press(input_file, total_button_presses)
+                );
             }
         }
     }
+
+    #[test]
+    fn test_huge_press_counts() {
+        let pulse_totals =
+            count_pulses(SECOND_EXAMPLE, &ModuleRegistry::default(), u64::MAX).unwrap();
+        //Every 4 presses send 17 low and 11 high pulses, and u64::MAX leaves 3 presses over.
+        let (leftover_low, leftover_high) = simulate_every_press(SECOND_EXAMPLE, 3);
+        let full_cycles = (u64::MAX / 4) as u128;
+        assert_eq!(pulse_totals.low_pulses, full_cycles * 17 + leftover_low);
+        assert_eq!(pulse_totals.high_pulses, full_cycles * 11 + leftover_high);
+    }
+
+    #[test]
+    fn test_longer_cycle() {
+        //The flip-flops count up in binary, so the network takes 8 presses to repeat.
+        let input_file = "broadcaster -> a
+%a -> b, con
+%b -> c, con
+%c -> con
+&con -> output";
+        let pulse_totals =
+            count_pulses(input_file, &ModuleRegistry::default(), 1_000_000_007).unwrap();
+        let (cycle_start, cycle_length) = pulse_totals.cycle.unwrap();
+        assert_eq!((cycle_start, cycle_length), (0, 8));
+        let presses = cycle_start + 3 * cycle_length + 1;
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), presses).unwrap();
+        assert_eq!(
+            (pulse_totals.low_pulses, pulse_totals.high_pulses),
+            simulate_every_press(input_file, presses)
+        );
+    }
+
+    #[test]
+    fn test_cycle_after_a_delay() {
+        //The delay line holds a Low pulse at first, and a High pulse after every press,
+        //so the cycle only starts after the first press.
+        let input_file = "broadcaster -> i
+!i -> d
+~d -> output";
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(pulse_totals.cycle, Some((1, 1)));
+        for total_button_presses in [0, 1, 2, 3, 1000] {
+            let pulse_totals =
+                count_pulses(input_file, &ModuleRegistry::default(), total_button_presses).unwrap();
+            assert_eq!(
+                (pulse_totals.low_pulses, pulse_totals.high_pulses),
+                simulate_every_press(input_file, total_button_presses)
+            );
+        }
+    }
+
+    #[test]
+    fn test_press_limit() {
+        //A counter whose period is twice the limit does not repeat a state within the limit,
+        //so counting more presses than the limit is refused.
+        let mut registry = ModuleRegistry::new();
+        registry
+            .register("#", || {
+                Box::new(Counter::new(MAX_SIMULATED_PRESSES as usize * 2))
+            })
+            .unwrap();
+        let input_file = "broadcaster -> c
+#c -> output";
+        assert!(count_pulses(input_file, &registry, MAX_SIMULATED_PRESSES + 1).is_err());
+    }
+
+    #[test]
+    fn test_inverter() {
+        //A conjunction with a single input is an inverter, so swapping it for one changes nothing.
+        let input_file = FIRST_EXAMPLE.replace("&inv", "!inv");
+        let pulse_totals = count_pulses(&input_file, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(
+            (pulse_totals.low_pulses, pulse_totals.high_pulses),
+            (8000, 4000)
+        );
+    }
+
+    #[test]
+    fn test_delay_line() {
+        //The flip-flop alternates between sending high and low pulses,
+        //which the delay line sends on one press later.
+        let input_file = "broadcaster -> a
+%a -> d
+~d -> output";
+        let mut module_map = PulseModule::from_input_file(input_file, &ModuleRegistry::default())
+            .unwrap()
+            .into_iter()
+            .map(|module| (module.name.clone(), module))
+            .collect::<HashMap<_, _>>();
+        let mut sent = Vec::new();
+        for _ in 0..4 {
+            let message = PulseMessage {
+                sender: "broadcaster".to_owned(),
+                state: PulseType::Low,
+                recipients: vec!["a".to_owned()],
+            };
+            let from_a = module_map
+                .get_mut("a")
+                .unwrap()
+                .react_to_new_pulse(&message)
+                .unwrap();
+            let from_d = module_map
+                .get_mut("d")
+                .unwrap()
+                .react_to_new_pulse(&from_a)
+                .unwrap();
+            sent.push((from_a.state, from_d.state));
+        }
+        use PulseType::{High, Low};
+        assert_eq!(
+            sent,
+            vec![(High, Low), (Low, High), (High, Low), (Low, High)]
+        );
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), 2).unwrap();
+        assert_eq!((pulse_totals.low_pulses, pulse_totals.high_pulses), (6, 2));
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(pulse_totals.cycle, Some((0, 2)));
+    }
+
+    #[test]
+    fn test_counter() {
+        let input_file = "broadcaster -> c
+#c -> output";
+        //Only every tenth press sends a high pulse.
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), 25).unwrap();
+        assert_eq!((pulse_totals.low_pulses, pulse_totals.high_pulses), (73, 2));
+        let pulse_totals = count_pulses(input_file, &ModuleRegistry::default(), 1000).unwrap();
+        assert_eq!(pulse_totals.cycle, Some((0, 10)));
+        //The decade counter can only be swapped for another counter in a new registry.
+        let mut registry = ModuleRegistry::default();
+        assert!(registry
+            .register("#", || Box::new(Counter::new(3)))
+            .is_err());
+        let mut registry = ModuleRegistry::new();
+        registry
+            .register("#", || Box::new(Counter::new(3)))
+            .unwrap();
+        let pulse_totals = count_pulses(input_file, &registry, 3).unwrap();
+        assert_eq!((pulse_totals.low_pulses, pulse_totals.high_pulses), (8, 1));
+        let pulse_totals = count_pulses(input_file, &registry, 1000).unwrap();
+        assert_eq!(pulse_totals.cycle, Some((0, 3)));
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = ModuleRegistry::new();
+        assert!(PulseModule::from_input_file(FIRST_EXAMPLE, &registry).is_err());
+        registry
+            .register("%", || {
+                Box::new(FlipFlop {
+                    state: PulseType::Low,
+                })
+            })
+            .unwrap();
+        registry.register("&", || Box::new(Inverter)).unwrap();
+        let pulse_totals = count_pulses(FIRST_EXAMPLE, &registry, 1000).unwrap();
+        assert_eq!(
+            (pulse_totals.low_pulses, pulse_totals.high_pulses),
+            (8000, 4000)
+        );
+        assert!(ModuleRegistry::default().build("?x").is_err());
+        assert!(PulseModule::from_input_file("broadcaster a", &registry).is_err());
+
+        //The longest prefix wins, even when a shorter one is registered after it.
+        let mut registry = ModuleRegistry::new();
+        registry.register("%%", || Box::new(Inverter)).unwrap();
+        registry
+            .register("%", || {
+                Box::new(FlipFlop {
+                    state: PulseType::Low,
+                })
+            })
+            .unwrap();
+        let (name, behaviour) = registry.build("%%a").unwrap();
+        assert_eq!(name, "a");
+        assert_eq!(format!("{:?}", behaviour), "Inverter");
+        let (name, behaviour) = registry.build("%b").unwrap();
+        assert_eq!(name, "b");
+        assert!(format!("{:?}", behaviour).starts_with("FlipFlop"));
+        assert!(registry.register("%%", || Box::new(Inverter)).is_err());
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{read_input_file, SolveAdvent};
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
 
 pub struct Day21;
 
@@ -226,6 +227,47 @@ impl Garden {
         panic!("Garden Map did not contain an S");
     }
 
+    fn step_distances(&self, start: (usize, usize)) -> Vec<Vec<Option<usize>>> {
+        //! The fewest steps from `start` to every garden plot, using a breadth first search.
+        //! Rocks, and plots that cannot be reached, are None.
+        let mut distances = vec![vec![None; self.column_count]; self.row_count];
+        distances[start.0][start.1] = Some(0);
+        let mut explorer_queue = VecDeque::from([(
+            GardenExplorer {
+                row: start.0,
+                column: start.1,
+                garden: self,
+            },
+            0,
+        )]);
+        while let Some((garden_explorer, steps)) = explorer_queue.pop_front() {
+            for next_explorer in garden_explorer.next_steps() {
+                let distance = &mut distances[next_explorer.row][next_explorer.column];
+                if distance.is_none() {
+                    *distance = Some(steps + 1);
+                    explorer_queue.push_back((next_explorer, steps + 1));
+                }
+            }
+        }
+        distances
+    }
+
+    fn parse_start(&self, start: &str) -> Result<(usize, usize), String> {
+        //! Parse a `row,column` starting point, which must be a garden plot on the map.
+        let invalid_start = || format!("Expected a start of the form row,column, got `{}`", start);
+        let (row, column) = start.split_once(',').ok_or_else(invalid_start)?;
+        let row = row.trim().parse::<usize>().map_err(|_| invalid_start())?;
+        let column = column
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| invalid_start())?;
+        match self.get_position(row, column) {
+            None => Err(format!("The start {},{} is not on the map", row, column)),
+            Some('#') => Err(format!("The start {},{} is a rock", row, column)),
+            Some(_) => Ok((row, column)),
+        }
+    }
+
     fn get_position(&self, row: usize, col: usize) -> Option<&char> {
         if let Some(row) = self.map.get(row) {
             if let Some(col_item) = row.get(col) {
@@ -237,22 +279,158 @@ impl Garden {
 }
 
 impl SolveAdvent for Day21 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let garden = Garden::new(&file_as_str);
         let position_of_s = garden.s_position();
-        find_accessable_garden_plots(64, garden, position_of_s);
+        find_accessable_garden_plots(64, garden, position_of_s).to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let garden = Garden::new(&file_as_str);
         let position_of_s = garden.s_position();
         find_accessable_garden_plots_infinite(1000, garden, position_of_s);
+        //1000 steps is far short of the step count the puzzle asks for, so there
+        //is no answer to return yet.
+        String::new()
+    }
+}
+
+pub fn heatmap(
+    path_to_file: &str,
+    start: Option<&str>,
+    steps: Option<usize>,
+    ppm_path: Option<&str>,
+) -> Result<(), String> {
+    //! Show how many steps it takes to reach every garden plot from the start, which is S unless
+    //! `start` gives a `row,column`. Plots that can be reached in exactly `steps` steps are
+    //! highlighted. The heatmap is printed as text, or written to `ppm_path` as a PPM image.
+    let file_as_str = read_input_file(path_to_file);
+    let garden = Garden::new(&file_as_str);
+    let start = match start {
+        Some(start) => garden.parse_start(start)?,
+        None => garden.s_position(),
+    };
+    let distances = garden.step_distances(start);
+    match ppm_path {
+        Some(ppm_path) => {
+            fs::write(ppm_path, render_heatmap_ppm(&garden, &distances, steps))
+                .map_err(|err| format!("Could not write {}: {}", ppm_path, err))?;
+            println!("Wrote the heatmap to {}", ppm_path);
+        }
+        None => print!("{}", render_heatmap_ascii(&garden, &distances, steps)),
+    }
+    if let Some(steps) = steps {
+        println!(
+            "{} garden plots can be reached in exactly {} steps",
+            count_reachable_in_exactly(&distances, steps),
+            steps
+        );
+    }
+    Ok(())
+}
+
+fn reachable_in_exactly(distance: usize, steps: usize) -> bool {
+    //! Every step changes whether `row + column` is odd or even, so a plot reached in `distance`
+    //! steps can be reached again in any larger number of steps with the same parity,
+    //! by stepping back and forth.
+    distance <= steps && (steps - distance).is_multiple_of(2)
+}
+
+fn count_reachable_in_exactly(distances: &[Vec<Option<usize>>], steps: usize) -> usize {
+    distances
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|&&distance| reachable_in_exactly(distance, steps))
+        .count()
+}
+
+fn render_heatmap_ascii(
+    garden: &Garden,
+    distances: &[Vec<Option<usize>>],
+    steps: Option<usize>,
+) -> String {
+    //! Draw each reachable plot as a digit from 0 (nearest) to 9 (furthest), scaled to the furthest plot.
+    //! Plots reachable in exactly `steps` steps are an `O` as in the puzzle, rocks are a `#`,
+    //! and plots that cannot be reached are a `.`.
+    let furthest = distances
+        .iter()
+        .flatten()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or(0);
+    let mut rendered = String::new();
+    for (row, distance_row) in distances.iter().enumerate() {
+        for (column, distance) in distance_row.iter().enumerate() {
+            let tile = match distance {
+                Some(distance)
+                    if steps.is_some_and(|steps| reachable_in_exactly(*distance, steps)) =>
+                {
+                    'O'
+                }
+                Some(distance) => {
+                    char::from_digit((distance * 10 / (furthest + 1)) as u32, 10).unwrap()
+                }
+                None if garden.map[row][column] == '#' => '#',
+                None => '.',
+            };
+            rendered.push(tile);
+        }
+        rendered.push('\n');
     }
+    rendered
 }
 
-fn find_accessable_garden_plots(steps: usize, garden: Garden, position_of_s: (usize, usize)) {
+fn render_heatmap_ppm(
+    garden: &Garden,
+    distances: &[Vec<Option<usize>>],
+    steps: Option<usize>,
+) -> String {
+    //! Draw the heatmap as a plain text PPM image, with one pixel per tile. Reachable plots fade
+    //! from blue (nearest) to red (furthest), plots reachable in exactly `steps` steps are white,
+    //! rocks are grey, and plots that cannot be reached are black.
+    let furthest = distances
+        .iter()
+        .flatten()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+    let mut ppm = format!("P3\n{} {}\n255\n", garden.column_count, garden.row_count);
+    for (row, distance_row) in distances.iter().enumerate() {
+        let pixels = distance_row
+            .iter()
+            .enumerate()
+            .map(|(column, distance)| match distance {
+                Some(distance)
+                    if steps.is_some_and(|steps| reachable_in_exactly(*distance, steps)) =>
+                {
+                    (255, 255, 255)
+                }
+                Some(distance) => {
+                    let heat = distance * 255 / furthest;
+                    (heat, 0, 255 - heat)
+                }
+                None if garden.map[row][column] == '#' => (96, 96, 96),
+                None => (0, 0, 0),
+            })
+            .map(|(red, green, blue)| format!("{} {} {}", red, green, blue))
+            .collect::<Vec<_>>();
+        ppm.push_str(&pixels.join(" "));
+        ppm.push('\n');
+    }
+    ppm
+}
+
+fn find_accessable_garden_plots(
+    steps: usize,
+    garden: Garden,
+    position_of_s: (usize, usize),
+) -> usize {
     //! Start with a `GardenExplorer` at the position of S, which is the start.
     //!For each step iteration, try to move each `GardenExplorer` in all 4 directions.
     //! Use a HashSet to remove all collisions, which prevents the exponential growth of
@@ -287,6 +465,7 @@ fn find_accessable_garden_plots(steps: usize, garden: Garden, position_of_s: (us
         steps,
         unique_garden_plot_tracker.len()
     );
+    unique_garden_plot_tracker.len()
 }
 
 fn find_accessable_garden_plots_infinite(
@@ -335,3 +514,96 @@ fn find_accessable_garden_plots_infinite(
         unique_garden_plot_tracker.len()
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn test_exactly_n_steps() {
+        let garden = Garden::new(EXAMPLE_INPUT);
+        let distances = garden.step_distances(garden.s_position());
+        let rendered = render_heatmap_ascii(&garden, &distances, Some(6));
+        //Without the heat, the plots reached in exactly 6 steps are as the puzzle shows.
+        let plots_reached = rendered
+            .chars()
+            .map(|tile| if tile.is_ascii_digit() { '.' } else { tile })
+            .collect::<String>();
+        assert_eq!(
+            plots_reached,
+            "...........
+.....###.#.
+.###.##.O#.
+.O#O#O.O#..
+O.O.#.#.O..
+.##O.O####.
+.##.O#O..#.
+.O.O.O.##..
+.##.#.####.
+.##O.##.##.
+...........
+"
+        );
+        for steps in 0..=20 {
+            assert_eq!(
+                count_reachable_in_exactly(&distances, steps),
+                find_accessable_garden_plots(steps, garden.clone(), garden.s_position())
+            );
+        }
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let garden = Garden::new("S..\n.#.\n..#");
+        let distances = garden.step_distances(garden.s_position());
+        assert_eq!(
+            distances,
+            vec![
+                vec![Some(0), Some(1), Some(2)],
+                vec![Some(1), None, Some(3)],
+                vec![Some(2), Some(3), None],
+            ]
+        );
+        assert_eq!(
+            render_heatmap_ascii(&garden, &distances, None),
+            "025\n2#7\n57#\n"
+        );
+        assert_eq!(
+            render_heatmap_ascii(&garden, &distances, Some(3)),
+            "0O5\nO#O\n5O#\n"
+        );
+        assert_eq!(
+            render_heatmap_ppm(&garden, &distances, Some(2)),
+            "P3
+3 3
+255
+255 255 255 85 0 170 255 255 255
+85 0 170 96 96 96 255 0 0
+255 255 255 255 0 0 96 96 96
+"
+        );
+        //A garden walled off from the start.
+        let garden = Garden::new("S#.\n#..\n...");
+        let distances = garden.step_distances((2, 2));
+        assert_eq!(
+            render_heatmap_ascii(&garden, &distances, None),
+            ".#6\n#63\n630\n"
+        );
+        assert_eq!(garden.parse_start("2,2"), Ok((2, 2)));
+        assert!(garden.parse_start("0,1").is_err());
+        assert!(garden.parse_start("3,0").is_err());
+        assert!(garden.parse_start("1").is_err());
+    }
+}
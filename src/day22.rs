@@ -1,17 +1,21 @@
 use crate::{read_input_file, SolveAdvent};
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Write};
 
 pub struct Day22;
 
 impl SolveAdvent for Day22 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let bricks = construct_bricks_from_file(path_to_file);
         let bricks = descend_bricks(bricks);
         let bricks_safe_to_remove = find_bricks_safe_to_disintegrate(bricks);
         println!("There are {} bricks safe to remove", bricks_safe_to_remove);
+        bricks_safe_to_remove.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let bricks = construct_bricks_from_file(path_to_file);
         let bricks = descend_bricks(bricks);
         let total_bricks_removed = find_sum_of_brick_chain_reaction(bricks);
@@ -19,13 +23,135 @@ impl SolveAdvent for Day22 {
             "Through chain reaction, a total of {} bricks can be removed",
             total_bricks_removed
         );
+        total_bricks_removed.to_string()
     }
 }
 
+pub fn render(path_to_file: &str, use_color: bool) {
+    //! Print the settled pile as seen from the side along the x and y axes, as the puzzle
+    //! illustrates it. If `use_color`, bricks are colored by how many other bricks would fall
+    //! if they were disintegrated.
+    let bricks = descend_bricks(construct_bricks_from_file(path_to_file));
+    let chain_reaction_sizes = use_color.then(|| find_chain_reaction_sizes(&bricks));
+    for axis in [Axis::X, Axis::Y] {
+        println!(
+            "{}",
+            render_side_view(&bricks, axis, chain_reaction_sizes.as_ref())
+        );
+    }
+}
+
+pub fn export_obj(path_to_file: &str, obj_path: &str, use_color: bool) -> Result<(), String> {
+    //! Write the settled pile to `obj_path` as a Wavefront OBJ model. If `use_color`, every
+    //! vertex is colored from green to red by the chain reaction size of its brick.
+    let bricks = descend_bricks(construct_bricks_from_file(path_to_file));
+    let chain_reaction_sizes = use_color.then(|| find_chain_reaction_sizes(&bricks));
+    fs::write(
+        obj_path,
+        bricks_to_obj(&bricks, chain_reaction_sizes.as_ref()),
+    )
+    .map_err(|err| format!("Could not write {}: {}", obj_path, err))?;
+    println!("Wrote {} bricks to {}", bricks.len(), obj_path);
+    Ok(())
+}
+
+pub fn query_disintegrations(path_to_file: &str) -> Result<(), String> {
+    //! Read sets of brick numbers from standard input, one set per line, and print which bricks
+    //! fall when every brick in the set is disintegrated at once. An empty line stops the queries.
+    if path_to_file == "-" {
+        return Err("Queries are read from standard input, so the input must be a file".to_owned());
+    }
+    let bricks = descend_bricks(construct_bricks_from_file(path_to_file));
+    if bricks.is_empty() {
+        return Err("There are no bricks to disintegrate".to_owned());
+    }
+    println!(
+        "Enter brick numbers to disintegrate together, such as `0,5`, from 0 to {}. An empty line stops.",
+        bricks.len() - 1
+    );
+    answer_queries(&bricks, io::stdin().lock(), io::stdout().lock())
+        .map_err(|err| format!("Could not answer queries: {}", err))
+}
+
+fn answer_queries(
+    settled_bricks: &[Brick],
+    queries: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    //! Answer each line of `queries` by writing the bricks that fall to `output`.
+    for query in queries.lines() {
+        let query = query?;
+        if query.trim().is_empty() {
+            break;
+        }
+        let uuids = query
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|uuid| !uuid.is_empty())
+            .map(|uuid| uuid.parse::<i32>())
+            .collect::<Result<HashSet<_>, _>>();
+        let fallen_bricks = match uuids {
+            Ok(uuids) => disintegrate_bricks(settled_bricks, &uuids),
+            Err(_) => Err(format!("`{}` is not a list of brick numbers", query.trim())),
+        };
+        match fallen_bricks {
+            Ok(fallen_bricks) => {
+                writeln!(output, "{} bricks fall", fallen_bricks.len())?;
+                for (before, after) in fallen_bricks {
+                    writeln!(output, "  {}: {} -> {}", before.uuid, before, after)?;
+                }
+            }
+            Err(err) => writeln!(output, "{}", err)?,
+        }
+    }
+    Ok(())
+}
+
+fn disintegrate_bricks(
+    settled_bricks: &[Brick],
+    uuids: &HashSet<i32>,
+) -> Result<Vec<(Brick, Brick)>, String> {
+    //! Disintegrate every brick in `uuids` at the same time, and let the rest of the pile settle again.
+    //! Returns each brick that fell, as it was before and where it came to rest, sorted by uuid.
+    if let Some(uuid) = uuids
+        .iter()
+        .find(|uuid| !settled_bricks.iter().any(|brick| brick.uuid == **uuid))
+    {
+        return Err(format!("There is no brick {}", uuid));
+    }
+    let mut remaining_bricks = settled_bricks
+        .iter()
+        .filter(|brick| !uuids.contains(&brick.uuid))
+        .copied()
+        .collect::<Vec<_>>();
+    if remaining_bricks.is_empty() {
+        return Ok(Vec::new());
+    }
+    //`descend_bricks` settles the lowest brick first, so it must be at the end.
+    remaining_bricks.sort_by_key(|brick| -brick.lower_bound.2);
+    let resettled_bricks = descend_bricks(remaining_bricks)
+        .into_iter()
+        .map(|brick| (brick.uuid, brick))
+        .collect::<HashMap<_, _>>();
+    let mut fallen_bricks = settled_bricks
+        .iter()
+        .filter_map(|before| {
+            let after = resettled_bricks.get(&before.uuid)?;
+            (after.lower_bound != before.lower_bound).then_some((*before, *after))
+        })
+        .collect::<Vec<_>>();
+    fallen_bricks.sort_by_key(|(before, _)| before.uuid);
+    Ok(fallen_bricks)
+}
+
 fn construct_bricks_from_file(fp: &str) -> Vec<Brick> {
     //! Construct a Vec of Bricks from the input file contents.
     //! This bricks are sorted based on the z-axis lower-bound, but are not descended.
     let file_contents = read_input_file(fp);
+    parse_bricks(&file_contents)
+}
+
+fn parse_bricks(file_contents: &str) -> Vec<Brick> {
+    //! Construct the Bricks from the lines of the input, sorted based on the z-axis lower-bound.
     let mut bricks = file_contents
         .lines()
         .zip((0..).cycle())
@@ -36,7 +162,7 @@ fn construct_bricks_from_file(fp: &str) -> Vec<Brick> {
 }
 
 ///Represents a single brick in the pile
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 struct Brick {
     ///a unique number useful for debugging purposes
     uuid: i32,
@@ -93,6 +219,19 @@ impl Brick {
         z_overlap1 || z_overlap2
     }
 
+    fn axis_bounds(&self, axis: Axis) -> (i32, i32) {
+        //! The lower and upper bound of the brick along the horizontal axis.
+        match axis {
+            Axis::X => (self.lower_bound.0, self.upper_bound.0),
+            Axis::Y => (self.lower_bound.1, self.upper_bound.1),
+        }
+    }
+
+    fn label(&self) -> char {
+        //! The letter the puzzle uses for the brick, which repeats after Z.
+        (b'A' + (self.uuid % 26) as u8) as char
+    }
+
     fn check_xy_overlap(&self, other: &Brick) -> bool {
         self.x_overlap(other) && self.y_overlap(other)
     }
@@ -129,6 +268,19 @@ impl Brick {
     }
 }
 
+impl fmt::Display for Brick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //! The brick in the same format as the input, such as `1,0,1~1,2,1`.
+        let (x_lower, y_lower, z_lower) = self.lower_bound;
+        let (x_upper, y_upper, z_upper) = self.upper_bound;
+        write!(
+            f,
+            "{},{},{}~{},{},{}",
+            x_lower, y_lower, z_lower, x_upper, y_upper, z_upper
+        )
+    }
+}
+
 fn lower_brick_as_far_as_possible(
     final_brick_positions: &mut Vec<Brick>,
     mut brick_to_descend: Brick,
@@ -240,6 +392,26 @@ fn find_bricks_safe_to_disintegrate(bricks: Vec<Brick>) -> i32 {
 fn find_sum_of_brick_chain_reaction(bricks: Vec<Brick>) -> i32 {
     //! Solve Part2 of the problem by simulating the chain reaction caused
     //! by removing a single brick.
+    find_chain_reaction_sizes(&bricks).values().sum()
+}
+
+fn find_chain_reaction_sizes(bricks: &[Brick]) -> HashMap<i32, i32> {
+    //! The number of other bricks that fall when each brick is removed on its own, keyed by uuid.
+    let brick_dependency_map = build_supporting_brick_map(bricks);
+    //Simulate the chain reaction of removing a single brick
+    bricks
+        .iter()
+        .map(|brick| {
+            (
+                brick.uuid,
+                brick_chain_reaction(brick.uuid, brick_dependency_map.clone()),
+            )
+        })
+        .collect::<HashMap<_, _>>()
+}
+
+fn build_supporting_brick_map(bricks: &[Brick]) -> HashMap<i32, HashSet<i32>> {
+    //! Map the uuid of every brick resting on other bricks to the uuids of the bricks holding it up.
 
     //Unlike the Part1 solution, here we want to key the brick_height_map
     //by each bricks upper_bound on the z-axis.
@@ -265,12 +437,7 @@ fn find_sum_of_brick_chain_reaction(bricks: Vec<Brick>) -> i32 {
         }
     }
 
-    //Simulate the chain reaction of removing a single brick
-    let mut total_bricks_removed = 0;
-    for brick in bricks.iter() {
-        total_bricks_removed += brick_chain_reaction(brick.uuid, brick_dependency_map.clone());
-    }
-    total_bricks_removed
+    brick_dependency_map
 }
 
 fn brick_chain_reaction(brick_to_remove: i32, mut brick_map: HashMap<i32, HashSet<i32>>) -> i32 {
@@ -295,3 +462,352 @@ fn brick_chain_reaction(brick_to_remove: i32, mut brick_map: HashMap<i32, HashSe
     }
     visited.len() as i32 - 1
 }
+
+///A horizontal axis that the pile can be viewed along.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    fn name(&self) -> char {
+        match self {
+            Axis::X => 'x',
+            Axis::Y => 'y',
+        }
+    }
+}
+
+fn chain_reaction_color(chain_reaction_size: i32, largest_chain_reaction: i32) -> &'static str {
+    //! The ANSI color for a brick: green if it is safe to disintegrate, yellow if up to
+    //! a quarter of the largest chain reaction falls, and red otherwise.
+    if chain_reaction_size == 0 {
+        "\x1b[32m"
+    } else if chain_reaction_size * 4 <= largest_chain_reaction {
+        "\x1b[33m"
+    } else {
+        "\x1b[31m"
+    }
+}
+
+fn render_side_view(
+    bricks: &[Brick],
+    axis: Axis,
+    chain_reaction_sizes: Option<&HashMap<i32, i32>>,
+) -> String {
+    //! Draw the bricks as seen from the side, with `axis` running left to right and z running
+    //! upwards, like the puzzle's illustrations. Each brick is drawn with its letter, and a `?`
+    //! where several bricks are lined up one behind the other. If `chain_reaction_sizes` are given,
+    //! each cell is colored by the largest chain reaction of the bricks in it.
+    let width = bricks
+        .iter()
+        .map(|brick| brick.axis_bounds(axis).1 + 1)
+        .max()
+        .unwrap_or(0);
+    let height = bricks
+        .iter()
+        .map(|brick| brick.upper_bound.2)
+        .max()
+        .unwrap_or(0);
+    let largest_chain_reaction = chain_reaction_sizes
+        .and_then(|sizes| sizes.values().max().copied())
+        .unwrap_or(0);
+
+    let mut rendered = format!("{:>1$}\n", axis.name(), width as usize / 2 + 1);
+    for column in 0..width {
+        rendered.push(char::from_digit((column % 10) as u32, 10).unwrap());
+    }
+    rendered.push('\n');
+    for z in (1..=height).rev() {
+        for column in 0..width {
+            let bricks_in_cell = bricks
+                .iter()
+                .filter(|brick| {
+                    let (lower, upper) = brick.axis_bounds(axis);
+                    (lower..=upper).contains(&column)
+                        && (brick.lower_bound.2..=brick.upper_bound.2).contains(&z)
+                })
+                .collect::<Vec<_>>();
+            let symbol = match bricks_in_cell.as_slice() {
+                [] => '.',
+                [brick] => brick.label(),
+                _ => '?',
+            };
+            match chain_reaction_sizes {
+                Some(sizes) if !bricks_in_cell.is_empty() => {
+                    let chain_reaction_size = bricks_in_cell
+                        .iter()
+                        .map(|brick| sizes[&brick.uuid])
+                        .max()
+                        .unwrap();
+                    rendered.push_str(&format!(
+                        "{}{}\x1b[0m",
+                        chain_reaction_color(chain_reaction_size, largest_chain_reaction),
+                        symbol
+                    ));
+                }
+                _ => rendered.push(symbol),
+            }
+        }
+        rendered.push_str(&format!(" {}", z));
+        if z == (height + 1) / 2 {
+            rendered.push_str(" z");
+        }
+        rendered.push('\n');
+    }
+    rendered.push_str(&"-".repeat(width as usize));
+    rendered.push_str(" 0\n");
+    rendered
+}
+
+fn bricks_to_obj(bricks: &[Brick], chain_reaction_sizes: Option<&HashMap<i32, i32>>) -> String {
+    //! Write every brick as a cuboid in the Wavefront OBJ format, in a group named after its uuid.
+    //! Each cube of the brick spans one unit, so a brick covers its bounds plus one. If
+    //! `chain_reaction_sizes` are given, the vertices carry an RGB color after their position,
+    //! which most OBJ viewers read.
+    let largest_chain_reaction = chain_reaction_sizes
+        .and_then(|sizes| sizes.values().max().copied())
+        .unwrap_or(0)
+        .max(1);
+    //The corners of a cuboid, where bit 0 picks the x bound, bit 1 the y bound and bit 2 the z bound.
+    //Each face lists its corners counter-clockwise when seen from outside the cuboid.
+    const FACES: [[usize; 4]; 6] = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    let mut sorted_bricks = bricks.iter().collect::<Vec<_>>();
+    sorted_bricks.sort_by_key(|brick| brick.uuid);
+
+    let mut obj = format!("# {} settled bricks\n", bricks.len());
+    for (brick_number, brick) in sorted_bricks.into_iter().enumerate() {
+        obj.push_str(&format!("g brick_{}\n", brick.uuid));
+        let color = chain_reaction_sizes.map(|sizes| {
+            let fraction = sizes[&brick.uuid] as f64 / largest_chain_reaction as f64;
+            format!(" {:.3} {:.3} 0.000", fraction, 1.0 - fraction)
+        });
+        for corner in 0..8 {
+            let pick = |bit: usize, lower: i32, upper: i32| {
+                if corner >> bit & 1 == 0 {
+                    lower
+                } else {
+                    upper + 1
+                }
+            };
+            obj.push_str(&format!(
+                "v {} {} {}{}\n",
+                pick(0, brick.lower_bound.0, brick.upper_bound.0),
+                pick(1, brick.lower_bound.1, brick.upper_bound.1),
+                pick(2, brick.lower_bound.2, brick.upper_bound.2),
+                color.as_deref().unwrap_or("")
+            ));
+        }
+        //OBJ vertex numbers start at 1, and count every vertex written so far.
+        let first_vertex = brick_number * 8 + 1;
+        for face in FACES {
+            let vertices = face
+                .iter()
+                .map(|corner| (first_vertex + corner).to_string())
+                .collect::<Vec<_>>();
+            obj.push_str(&format!("f {}\n", vertices.join(" ")));
+        }
+    }
+    obj
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn test_example() {
+        let bricks = descend_bricks(parse_bricks(EXAMPLE_INPUT));
+        assert_eq!(find_bricks_safe_to_disintegrate(bricks.clone()), 5);
+        assert_eq!(find_sum_of_brick_chain_reaction(bricks.clone()), 7);
+        let chain_reaction_sizes = find_chain_reaction_sizes(&bricks);
+        assert_eq!(chain_reaction_sizes[&0], 6);
+        assert_eq!(chain_reaction_sizes[&5], 1);
+    }
+
+    #[test]
+    fn test_side_views() {
+        let bricks = parse_bricks(EXAMPLE_INPUT);
+        assert_eq!(
+            render_side_view(&bricks, Axis::X, None),
+            " x
+012
+.G. 9
+.G. 8
+... 7
+FFF 6
+..E 5 z
+D.. 4
+CCC 3
+BBB 2
+.A. 1
+--- 0
+"
+        );
+        assert_eq!(
+            render_side_view(&bricks, Axis::Y, None),
+            " y
+012
+.G. 9
+.G. 8
+... 7
+.F. 6
+EEE 5 z
+DDD 4
+..C 3
+B.. 2
+AAA 1
+--- 0
+"
+        );
+        let bricks = descend_bricks(bricks);
+        assert_eq!(
+            render_side_view(&bricks, Axis::X, None),
+            " x
+012
+.G. 6
+.G. 5
+FFF 4
+D.E 3 z
+??? 2
+.A. 1
+--- 0
+"
+        );
+        assert_eq!(
+            render_side_view(&bricks, Axis::Y, None),
+            " y
+012
+.G. 6
+.G. 5
+.F. 4
+??? 3 z
+B.C 2
+AAA 1
+--- 0
+"
+        );
+        let chain_reaction_sizes = find_chain_reaction_sizes(&bricks);
+        let colored = render_side_view(&bricks, Axis::Y, Some(&chain_reaction_sizes));
+        let last_row = colored.lines().nth(7).unwrap();
+        assert_eq!(
+            last_row,
+            "\x1b[31mA\x1b[0m\x1b[31mA\x1b[0m\x1b[31mA\x1b[0m 1"
+        );
+        let top_row = colored.lines().nth(2).unwrap();
+        assert_eq!(top_row, ".\x1b[32mG\x1b[0m. 6");
+    }
+
+    #[test]
+    fn test_obj() {
+        let bricks = descend_bricks(parse_bricks(EXAMPLE_INPUT));
+        let obj = bricks_to_obj(&bricks, None);
+        assert_eq!(obj.lines().filter(|line| line.starts_with("g ")).count(), 7);
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            56
+        );
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("f ")).count(),
+            42
+        );
+        let first_brick = obj.lines().skip(1).take(15).collect::<Vec<_>>();
+        assert_eq!(
+            first_brick,
+            vec![
+                "g brick_0",
+                "v 1 0 1",
+                "v 2 0 1",
+                "v 1 3 1",
+                "v 2 3 1",
+                "v 1 0 2",
+                "v 2 0 2",
+                "v 1 3 2",
+                "v 2 3 2",
+                "f 1 3 4 2",
+                "f 5 6 8 7",
+                "f 1 2 6 5",
+                "f 3 7 8 4",
+                "f 1 5 7 3",
+                "f 2 4 8 6",
+            ]
+        );
+        assert!(obj.contains("\nf 49 51 52 50\n"));
+        let chain_reaction_sizes = find_chain_reaction_sizes(&bricks);
+        let colored_obj = bricks_to_obj(&bricks, Some(&chain_reaction_sizes));
+        assert!(colored_obj.contains("\nv 1 0 1 1.000 0.000 0.000\n"));
+        assert!(colored_obj.contains("\nv 1 1 5 0.000 1.000 0.000\n"));
+    }
+
+    #[test]
+    fn test_disintegrate_bricks() {
+        let bricks = descend_bricks(parse_bricks(EXAMPLE_INPUT));
+        //Removing a single brick matches the chain reaction sizes.
+        let chain_reaction_sizes = find_chain_reaction_sizes(&bricks);
+        for brick in bricks.iter() {
+            let fallen_bricks = disintegrate_bricks(&bricks, &HashSet::from([brick.uuid])).unwrap();
+            assert_eq!(
+                fallen_bricks.len() as i32,
+                chain_reaction_sizes[&brick.uuid]
+            );
+        }
+        //Without B and C, D and E fall to the ground beside A, and F and G follow them down.
+        let fallen_bricks = disintegrate_bricks(&bricks, &HashSet::from([1, 2])).unwrap();
+        let moves = fallen_bricks
+            .iter()
+            .map(|(before, after)| format!("{} {} -> {}", before.uuid, before, after))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            moves,
+            vec![
+                "3 0,0,3~0,2,3 -> 0,0,1~0,2,1",
+                "4 2,0,3~2,2,3 -> 2,0,1~2,2,1",
+                "5 0,1,4~2,1,4 -> 0,1,2~2,1,2",
+                "6 1,1,5~1,1,6 -> 1,1,3~1,1,4",
+            ]
+        );
+        //D and E are each safe to remove, but not both at once.
+        let fallen_bricks = disintegrate_bricks(&bricks, &HashSet::from([3, 4])).unwrap();
+        assert_eq!(fallen_bricks.len(), 2);
+        assert_eq!(fallen_bricks[0].1.lower_bound, (0, 1, 2));
+        assert!(disintegrate_bricks(&bricks, &HashSet::from([7])).is_err());
+        assert_eq!(
+            disintegrate_bricks(&bricks, &(0..7).collect()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_answer_queries() {
+        let bricks = descend_bricks(parse_bricks(EXAMPLE_INPUT));
+        let mut output = Vec::new();
+        answer_queries(&bricks, "5\n3 4\nG\n9\n\n0\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1 bricks fall
+  6: 1,1,5~1,1,6 -> 1,1,2~1,1,3
+2 bricks fall
+  5: 0,1,4~2,1,4 -> 0,1,2~2,1,2
+  6: 1,1,5~1,1,6 -> 1,1,3~1,1,4
+`G` is not a list of brick numbers
+There is no brick 9
+"
+        );
+    }
+}
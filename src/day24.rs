@@ -1,55 +1,294 @@
+use crate::math::Rational;
 use crate::{read_input_file, SolveAdvent};
 
 pub struct Day24;
 
 #[derive(Debug, Clone)]
 struct Hailstone {
-    x: f64,
-    y: f64,
-    _z: f64,
-    vx: f64,
-    vy: f64,
-    _vz: f64,
+    x: i128,
+    y: i128,
+    z: i128,
+    vx: i128,
+    vy: i128,
+    vz: i128,
+}
+
+///How the future paths of two hailstones meet in the xy-plane.
+#[derive(Debug, Clone, PartialEq)]
+enum PathCrossing {
+    ///The paths never meet in the future, either because they are parallel,
+    /// or because they crossed in the past.
+    Never,
+    ///The paths meet at exactly one `(x,y)` point.
+    Point(Rational, Rational),
+    ///The paths are collinear and share a segment, running from `from_t` to `until_t`
+    /// along the path of the first hailstone. An `until_t` of None means the overlap never ends.
+    Overlap {
+        from_t: Rational,
+        until_t: Option<Rational>,
+    },
+}
+
+///How two hailstones meet in 3D, where both must be in the same place at the same time.
+#[derive(Debug, Clone, PartialEq)]
+enum Encounter {
+    ///The hailstones are in the same place at time `t`, which is never in the past.
+    Collision { t: Rational },
+    ///The hailstones never collide, and are closest together at time `t`, when the square of
+    /// the distance between them is `distance_squared`. Hailstones that stay the same distance
+    /// apart are closest at time 0.
+    ClosestApproach {
+        t: Rational,
+        distance_squared: Rational,
+    },
+}
+
+///How the paths of two hailstones line up in 3D, ignoring when the hailstones are on them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathAlignment {
+    ///The paths run in the same or opposite directions, on different lines.
+    Parallel,
+    ///The paths are on the same line.
+    Collinear,
+}
+
+fn difference(a: [i128; 3], b: [i128; 3]) -> Option<[i128; 3]> {
+    //! `a - b`, or None if it overflows.
+    Some([
+        a[0].checked_sub(b[0])?,
+        a[1].checked_sub(b[1])?,
+        a[2].checked_sub(b[2])?,
+    ])
+}
+
+fn dot(a: [i128; 3], b: [i128; 3]) -> Option<i128> {
+    //! The dot product, or None if it overflows.
+    a[0].checked_mul(b[0])?
+        .checked_add(a[1].checked_mul(b[1])?)?
+        .checked_add(a[2].checked_mul(b[2])?)
+}
+
+fn cross(a: [i128; 3], b: [i128; 3]) -> Option<[i128; 3]> {
+    //! The cross product, or None if it overflows.
+    let component =
+        |i: usize, j: usize| a[i].checked_mul(b[j])?.checked_sub(a[j].checked_mul(b[i])?);
+    Some([component(1, 2)?, component(2, 0)?, component(0, 1)?])
 }
 
 impl Hailstone {
+    fn position(&self) -> [i128; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn velocity(&self) -> [i128; 3] {
+        [self.vx, self.vy, self.vz]
+    }
+
+    fn position_at(&self, t: Rational) -> Option<[Rational; 3]> {
+        //! The exact `(x,y,z)` position of the hailstone at time `t`.
+        let (x, y) = self.position_x_y_at(t)?;
+        let z = Rational::from_integer(self.z)
+            .checked_add(t.checked_mul(Rational::from_integer(self.vz))?)?;
+        Some([x, y, z])
+    }
+
+    fn encounter(&self, other: &Self) -> Option<Encounter> {
+        //! Find when the two hailstones collide in 3D, or when they are closest if they never do.
+        //! Returns None only if the arithmetic overflows.
+        //!
+        //! Relative to `self`, `other` is at `dp + dv * t`, where `dp` and `dv` are the differences in
+        //! position and velocity. The squared distance `|dp + dv * t|^2` is smallest at
+        //! `t = -(dp . dv) / |dv|^2`, when the squared distance is `|dp x dv|^2 / |dv|^2`.
+        //! They collide if that distance is 0.
+        let now = Rational::from_integer(0);
+        let dp = difference(other.position(), self.position())?;
+        let dv = difference(other.velocity(), self.velocity())?;
+        let dv_squared = dot(dv, dv)?;
+        let approach_t = if dv_squared == 0 {
+            //The hailstones move together, so they are always the same distance apart.
+            now
+        } else {
+            Rational::new(dot(dp, dv)?.checked_neg()?, dv_squared)?
+        };
+        //If the hailstones are already moving apart, they are closest now.
+        if approach_t <= now {
+            let dp_squared = dot(dp, dp)?;
+            if dp_squared == 0 {
+                return Some(Encounter::Collision { t: now });
+            }
+            return Some(Encounter::ClosestApproach {
+                t: now,
+                distance_squared: Rational::from_integer(dp_squared),
+            });
+        }
+        let perpendicular = cross(dp, dv)?;
+        let perpendicular_squared = dot(perpendicular, perpendicular)?;
+        if perpendicular_squared == 0 {
+            return Some(Encounter::Collision { t: approach_t });
+        }
+        Some(Encounter::ClosestApproach {
+            t: approach_t,
+            distance_squared: Rational::new(perpendicular_squared, dv_squared)?,
+        })
+    }
+
+    fn path_alignment(&self, other: &Self) -> Option<PathAlignment> {
+        //! Whether the 3D paths of the hailstones are parallel or collinear. Returns None for
+        //! any other paths, and for hailstones that do not move, which have no direction.
+        let (velocity, other_velocity) = (self.velocity(), other.velocity());
+        if velocity == [0; 3] || other_velocity == [0; 3] {
+            return None;
+        }
+        if cross(velocity, other_velocity)? != [0; 3] {
+            return None;
+        }
+        let dp = difference(other.position(), self.position())?;
+        if cross(dp, velocity)? == [0; 3] {
+            Some(PathAlignment::Collinear)
+        } else {
+            Some(PathAlignment::Parallel)
+        }
+    }
+
     #[allow(dead_code)]
-    fn move_t(&mut self, t: f64) {
+    fn move_t(&mut self, t: i128) {
         self.x += t * self.vx;
         self.y += t * self.vy;
-        self._z += t * self._vz;
+        self.z += t * self.vz;
+    }
+    fn position_x_y_at(&self, t: Rational) -> Option<(Rational, Rational)> {
+        //! The exact `(x,y)` position of the hailstone at time `t`.
+        let x = Rational::from_integer(self.x)
+            .checked_add(t.checked_mul(Rational::from_integer(self.vx))?)?;
+        let y = Rational::from_integer(self.y)
+            .checked_add(t.checked_mul(Rational::from_integer(self.vy))?)?;
+        Some((x, y))
     }
-    fn future_x_y_collision(&self, other: &Self) -> Option<(f64, f64)> {
-        //! Check if the two hailstones collide, excludingt the Z-axis, in the
-        //! future for the two hailstones. If they do collide, returns the `(x,y)`
-        //! pair of the collision.
+
+    fn future_x_y_collision(&self, other: &Self) -> PathCrossing {
+        //! Check if the future paths of the two hailstones cross, excluding the Z-axis.
+        //! Only the paths need to cross, the hailstones do not need to be there at the same time.
         //!
-        //! If equation 1 is: `y = ax + b`
-        //! and equation 2 is `y = cx + d`,
-        //! then it can be shown that `x_col = (d-b)/ (a-c)`
-        //! where `a != c`
-
-        //Assign a,c,d,c to make the logic easier to reason about
-        let a = self.vy / self.vx;
-        let b = self.y - (self.vy * self.x / self.vx);
-        let c = other.vy / other.vx;
-        let d = other.y - (other.vy * other.x / other.vx);
-        //Corresponds to parallel lines, which never collide
-        if a == c {
-            return None;
+        //! Writing each path as `p + v*t`, the paths cross when
+        //! `self.p + self.v * t1 = other.p + other.v * t2`. By Cramer's rule,
+        //! `t1 = (dx * other.vy - dy * other.vx) / det` and `t2 = (dx * self.vy - dy * self.vx) / det`,
+        //! where `(dx, dy) = other.p - self.p` and `det = self.vx * other.vy - self.vy * other.vx`.
+        //! Unlike comparing slopes, this has no trouble with vertical paths.
+        let det = self.vx * other.vy - self.vy * other.vx;
+        let (dx, dy) = (other.x - self.x, other.y - self.y);
+        if det == 0 {
+            return self.parallel_x_y_collision(other);
         }
+        let (Some(t_collision_self), Some(t_collision_other)) = (
+            Rational::new(dx * other.vy - dy * other.vx, det),
+            Rational::new(dx * self.vy - dy * self.vx, det),
+        ) else {
+            return PathCrossing::Never;
+        };
 
-        let x_collision = (d - b) / (a - c);
+        //Check that the collision is in the future for both particles.
+        let now = Rational::from_integer(0);
+        if t_collision_self < now || t_collision_other < now {
+            return PathCrossing::Never;
+        }
+        match self.position_x_y_at(t_collision_self) {
+            Some((x, y)) => PathCrossing::Point(x, y),
+            None => PathCrossing::Never,
+        }
+    }
 
-        //Check that x_collision is in the future for both particles.
-        let t_x_collision_self = (x_collision - self.x) / self.vx;
-        let t_x_collision_other = (x_collision - other.x) / other.vx;
-        if t_x_collision_self < 0.0 || t_x_collision_other < 0.0 {
-            return None;
+    fn parallel_x_y_collision(&self, other: &Self) -> PathCrossing {
+        //! Handles paths with parallel velocities, which only cross if they are collinear.
+        //! The overlap is described using times along the path of `self`.
+        let (dx, dy) = (other.x - self.x, other.y - self.y);
+        let self_speed_squared = self.vx * self.vx + self.vy * self.vy;
+        let other_speed_squared = other.vx * other.vx + other.vy * other.vy;
+        let now = Rational::from_integer(0);
+        if self_speed_squared == 0 {
+            //`self` never moves, so it is a single point that the path of other must reach.
+            let reaches_self = other.vx * dy - other.vy * dx == 0
+                && (dx == 0 && dy == 0 || -(dx * other.vx + dy * other.vy) > 0);
+            if !reaches_self {
+                return PathCrossing::Never;
+            }
+            return PathCrossing::Point(
+                Rational::from_integer(self.x),
+                Rational::from_integer(self.y),
+            );
         }
+        //Distinct parallel lines never meet.
+        if self.vx * dy - self.vy * dx != 0 {
+            return PathCrossing::Never;
+        }
+        //The time along the path of `self` at which it reaches the start of the path of other.
+        let Some(t_other_start) = Rational::new(dx * self.vx + dy * self.vy, self_speed_squared)
+        else {
+            return PathCrossing::Never;
+        };
+        let travelling_same_way = other.vx * self.vx + other.vy * self.vy > 0;
+        let (from_t, until_t) = if other_speed_squared == 0 {
+            (t_other_start, Some(t_other_start))
+        } else if travelling_same_way {
+            (t_other_start.max(now), None)
+        } else {
+            (now, Some(t_other_start))
+        };
+        match until_t {
+            Some(until_t) if until_t < from_t || from_t < now => PathCrossing::Never,
+            Some(until_t) if until_t == from_t => match self.position_x_y_at(from_t) {
+                Some((x, y)) => PathCrossing::Point(x, y),
+                None => PathCrossing::Never,
+            },
+            _ => PathCrossing::Overlap { from_t, until_t },
+        }
+    }
 
-        let y_collision = a * x_collision + b;
-        Some((x_collision, y_collision))
+    fn crossing_in_area(
+        &self,
+        crossing: &PathCrossing,
+        (x_col_lower, x_col_upper): (i128, i128),
+        (y_col_lower, y_col_upper): (i128, i128),
+    ) -> bool {
+        //! Whether any part of the crossing between `self` and another hailstone is
+        //! inside the target area.
+        let in_range = |value: Rational, lower: i128, upper: i128| {
+            Rational::from_integer(lower) <= value && value <= Rational::from_integer(upper)
+        };
+        match *crossing {
+            PathCrossing::Never => false,
+            PathCrossing::Point(x, y) => {
+                in_range(x, x_col_lower, x_col_upper) && in_range(y, y_col_lower, y_col_upper)
+            }
+            PathCrossing::Overlap { from_t, until_t } => {
+                //Clip the times of the overlap to the times `self` spends inside the
+                //area on each axis.
+                let mut earliest = from_t;
+                let mut latest = until_t;
+                for (position, velocity, lower, upper) in [
+                    (self.x, self.vx, x_col_lower, x_col_upper),
+                    (self.y, self.vy, y_col_lower, y_col_upper),
+                ] {
+                    if velocity == 0 {
+                        if position < lower || position > upper {
+                            return false;
+                        }
+                        continue;
+                    }
+                    let (Some(t_lower), Some(t_upper)) = (
+                        Rational::new(lower - position, velocity),
+                        Rational::new(upper - position, velocity),
+                    ) else {
+                        return false;
+                    };
+                    earliest = earliest.max(t_lower.min(t_upper));
+                    latest = Some(match latest {
+                        Some(latest) => latest.min(t_lower.max(t_upper)),
+                        None => t_lower.max(t_upper),
+                    });
+                }
+                latest.is_none_or(|latest| earliest <= latest)
+            }
+        }
     }
 
     fn from_line(line: &str) -> Hailstone {
@@ -60,63 +299,147 @@ impl Hailstone {
             .unwrap()
             .trim()
             .split(',')
-            .map(|num| num.trim().parse::<i64>().unwrap() as f64)
+            .map(|num| num.trim().parse::<i128>().unwrap())
             .collect::<Vec<_>>();
         let velocities = position_velocity_split
             .next()
             .unwrap()
             .trim()
             .split(',')
-            .map(|num| num.trim().parse::<i64>().unwrap() as f64)
+            .map(|num| num.trim().parse::<i128>().unwrap())
             .collect::<Vec<_>>();
-        let [vx, vy, vz]: [f64; 3] = velocities.try_into().unwrap();
-        let [x, y, z]: [f64; 3] = positions.try_into().unwrap();
+        let [vx, vy, vz]: [i128; 3] = velocities.try_into().unwrap();
+        let [x, y, z]: [i128; 3] = positions.try_into().unwrap();
         Hailstone {
             x,
             y,
-            _z: z,
+            z,
             vx,
             vy,
-            _vz: vz,
+            vz,
         }
     }
 }
 
 impl SolveAdvent for Day24 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let hailstones = file_as_str
             .lines()
             .map(Hailstone::from_line)
             .collect::<Vec<_>>();
-        let collision_box = (200000000000000.0, 400000000000000.0);
-        count_collisions_part_1(&hailstones, collision_box, collision_box);
+        let collision_box = (200000000000000, 400000000000000);
+        count_collisions_part_1(&hailstones, collision_box, collision_box).to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let _ = path_to_file;
+        String::new()
     }
 }
 
+pub fn report_encounters(path_to_file: &str) {
+    //! Print every pair of hailstones that collide in 3D, every pair with parallel or collinear
+    //! paths, and when and how closely every other pair comes closest. Hailstones are numbered
+    //! from 1 in the order of the input.
+    let file_as_str = read_input_file(path_to_file);
+    let hailstones = file_as_str
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(Hailstone::from_line)
+        .collect::<Vec<_>>();
+    for line in describe_encounters(&hailstones) {
+        println!("{}", line);
+    }
+}
+
+fn describe_encounters(hailstones: &[Hailstone]) -> Vec<String> {
+    //! A line for how every pair of hailstones meets, ending with the pair that comes
+    //! closest without colliding.
+    let describe_distance = |distance_squared: Rational| {
+        let distance =
+            (distance_squared.numerator() as f64 / distance_squared.denominator() as f64).sqrt();
+        format!(
+            "a squared distance of {} (about {:.3})",
+            distance_squared, distance
+        )
+    };
+    let mut lines = Vec::new();
+    let mut closest_approach: Option<(usize, usize, Rational, Rational)> = None;
+    for (hailstone_num, hailstone) in hailstones.iter().enumerate() {
+        for (hailstone_num2, hailstone2) in hailstones.iter().enumerate().skip(hailstone_num + 1) {
+            let (number, number2) = (hailstone_num + 1, hailstone_num2 + 1);
+            match hailstone.path_alignment(hailstone2) {
+                Some(PathAlignment::Parallel) => lines.push(format!(
+                    "Hailstones {} and {} have parallel paths",
+                    number, number2
+                )),
+                Some(PathAlignment::Collinear) => lines.push(format!(
+                    "Hailstones {} and {} have collinear paths",
+                    number, number2
+                )),
+                None => {}
+            }
+            match hailstone.encounter(hailstone2) {
+                Some(Encounter::Collision { t }) => {
+                    let position = hailstone
+                        .position_at(t)
+                        .map(|[x, y, z]| format!("({}, {}, {})", x, y, z))
+                        .unwrap_or_else(|| "a position too large to compute".to_owned());
+                    lines.push(format!(
+                        "Hailstones {} and {} collide at t={} at {}",
+                        number, number2, t, position
+                    ));
+                }
+                Some(Encounter::ClosestApproach {
+                    t,
+                    distance_squared,
+                }) => {
+                    lines.push(format!(
+                        "Hailstones {} and {} never collide, and come closest at t={}, with {}",
+                        number,
+                        number2,
+                        t,
+                        describe_distance(distance_squared)
+                    ));
+                    if closest_approach.is_none_or(|(_, _, _, closest)| distance_squared < closest)
+                    {
+                        closest_approach = Some((number, number2, t, distance_squared));
+                    }
+                }
+                None => lines.push(format!(
+                    "Hailstones {} and {} are too far apart to compare exactly",
+                    number, number2
+                )),
+            }
+        }
+    }
+    if let Some((number, number2, t, distance_squared)) = closest_approach {
+        lines.push(format!(
+            "Of the hailstones that never collide, {} and {} come closest at t={}, with {}",
+            number,
+            number2,
+            t,
+            describe_distance(distance_squared)
+        ));
+    }
+    lines
+}
+
 fn count_collisions_part_1(
     hailstones: &[Hailstone],
-    (x_col_lower, x_col_upper): (f64, f64),
-    (y_col_lower, y_col_upper): (f64, f64),
-) {
+    x_col_bounds: (i128, i128),
+    y_col_bounds: (i128, i128),
+) -> usize {
     //! Count all hailstone collisions that occur within the required
     //! target area.
     let mut future_hailstone_collisions = 0;
     for (hailstone_num, hailstone) in hailstones.iter().enumerate() {
         for (hailstone_num2, hailstone2) in hailstones.iter().enumerate() {
             if hailstone_num < hailstone_num2 {
-                if let Some((col_x, col_y)) = hailstone.future_x_y_collision(hailstone2) {
-                    if x_col_lower <= col_x
-                        && col_x <= x_col_upper
-                        && y_col_lower <= col_y
-                        && col_y <= y_col_upper
-                    {
-                        future_hailstone_collisions += 1;
-                    }
+                let crossing = hailstone.future_x_y_collision(hailstone2);
+                if hailstone.crossing_in_area(&crossing, x_col_bounds, y_col_bounds) {
+                    future_hailstone_collisions += 1;
                 }
             }
         }
@@ -125,4 +448,268 @@ fn count_collisions_part_1(
         "Total hailstone crossings part1 is {}",
         future_hailstone_collisions
     );
+    future_hailstone_collisions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rational(numerator: i128, denominator: i128) -> Rational {
+        Rational::new(numerator, denominator).unwrap()
+    }
+
+    #[test]
+    fn test_example_crossings() {
+        let example = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+        let hailstones = example
+            .lines()
+            .map(Hailstone::from_line)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hailstones[0].future_x_y_collision(&hailstones[1]),
+            PathCrossing::Point(rational(43, 3), rational(46, 3))
+        );
+        //Parallel paths
+        assert_eq!(
+            hailstones[1].future_x_y_collision(&hailstones[2]),
+            PathCrossing::Never
+        );
+        //Crossed in the past for one hailstone.
+        assert_eq!(
+            hailstones[0].future_x_y_collision(&hailstones[4]),
+            PathCrossing::Never
+        );
+        assert_eq!(count_collisions_part_1(&hailstones, (7, 27), (7, 27)), 2);
+    }
+
+    #[test]
+    fn test_vertical_paths() {
+        let vertical = Hailstone::from_line("5, 0, 0 @ 0, 3, 0");
+        let diagonal = Hailstone::from_line("0, 0, 0 @ 1, 1, 0");
+        assert_eq!(
+            vertical.future_x_y_collision(&diagonal),
+            PathCrossing::Point(rational(5, 1), rational(5, 1))
+        );
+        assert_eq!(
+            diagonal.future_x_y_collision(&vertical),
+            PathCrossing::Point(rational(5, 1), rational(5, 1))
+        );
+        let other_vertical = Hailstone::from_line("6, 0, 0 @ 0, -1, 0");
+        assert_eq!(
+            vertical.future_x_y_collision(&other_vertical),
+            PathCrossing::Never
+        );
+    }
+
+    #[test]
+    fn test_collinear_paths() {
+        let leader = Hailstone::from_line("10, 10, 0 @ 2, 2, 0");
+        let follower = Hailstone::from_line("0, 0, 0 @ 1, 1, 0");
+        //Travelling the same way, the paths share everything from the leader onwards.
+        assert_eq!(
+            follower.future_x_y_collision(&leader),
+            PathCrossing::Overlap {
+                from_t: rational(10, 1),
+                until_t: None
+            }
+        );
+        //Travelling towards each other, the paths share the segment between them.
+        let oncoming = Hailstone::from_line("10, 10, 0 @ -3, -3, 0");
+        assert_eq!(
+            follower.future_x_y_collision(&oncoming),
+            PathCrossing::Overlap {
+                from_t: rational(0, 1),
+                until_t: Some(rational(10, 1))
+            }
+        );
+        //Travelling away from each other, the paths never meet.
+        let departing = Hailstone::from_line("-1, -1, 0 @ -1, -1, 0");
+        assert_eq!(
+            follower.future_x_y_collision(&departing),
+            PathCrossing::Never
+        );
+        //Starting back to back, the paths only share the starting point.
+        let back_to_back = Hailstone::from_line("0, 0, 0 @ -1, -1, 0");
+        assert_eq!(
+            follower.future_x_y_collision(&back_to_back),
+            PathCrossing::Point(rational(0, 1), rational(0, 1))
+        );
+    }
+
+    #[test]
+    fn test_crossings_in_area() {
+        let follower = Hailstone::from_line("0, 0, 0 @ 1, 1, 0");
+        let oncoming = Hailstone::from_line("10, 10, 0 @ -3, -3, 0");
+        let leader = Hailstone::from_line("10, 10, 0 @ 2, 2, 0");
+        //The overlap between the follower and oncoming ends at 10, before the area.
+        let hailstones = [follower.clone(), oncoming];
+        assert_eq!(count_collisions_part_1(&hailstones, (11, 20), (11, 20)), 0);
+        assert_eq!(count_collisions_part_1(&hailstones, (10, 20), (5, 20)), 1);
+        //The overlap with the leader never ends, so it reaches any area further along the path.
+        let hailstones = [follower, leader];
+        assert_eq!(
+            count_collisions_part_1(&hailstones, (100, 200), (150, 300)),
+            1
+        );
+        assert_eq!(
+            count_collisions_part_1(&hailstones, (100, 200), (201, 300)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_crossing_on_area_boundary() {
+        //These paths cross exactly on the lower boundary at x = 200000000000000,
+        //with a y-coordinate just below the lower boundary.
+        let hailstone1 = Hailstone::from_line("199999999999997, 199999999999999, 0 @ 3, 0, 0");
+        let hailstone2 = Hailstone::from_line("200000000000000, 199999999999990, 0 @ 0, 29, 0");
+        let crossing = hailstone1.future_x_y_collision(&hailstone2);
+        assert_eq!(
+            crossing,
+            PathCrossing::Point(rational(200000000000000, 1), rational(199999999999999, 1))
+        );
+        let area = (200000000000000, 400000000000000);
+        assert!(!hailstone1.crossing_in_area(&crossing, area, area));
+        let hailstone3 = Hailstone::from_line("200000000000000, 199999999999998, 0 @ 0, 3, 0");
+        let hailstone4 = Hailstone::from_line("199999999999998, 200000000000001, 0 @ 3, -2, 0");
+        let crossing = hailstone3.future_x_y_collision(&hailstone4);
+        assert_eq!(
+            crossing,
+            PathCrossing::Point(rational(200000000000000, 1), rational(599999999999999, 3))
+        );
+        //The y-coordinate is only a third below the lower boundary.
+        assert!(!hailstone3.crossing_in_area(&crossing, area, area));
+        assert!(hailstone3.crossing_in_area(&crossing, area, (199999999999999, 400000000000000)));
+    }
+
+    #[test]
+    fn test_encounters() {
+        let example = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+        //The rock thrown in part 2 of the puzzle hits every hailstone.
+        let rock = Hailstone::from_line("24, 13, 10 @ -3, 1, 2");
+        let collision_times = example
+            .lines()
+            .map(|line| rock.encounter(&Hailstone::from_line(line)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            collision_times,
+            [5, 3, 4, 6, 1]
+                .into_iter()
+                .map(|t| Encounter::Collision { t: rational(t, 1) })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            rock.position_at(rational(5, 1)),
+            Some([rational(9, 1), rational(18, 1), rational(20, 1)])
+        );
+        //A collision between whole time steps.
+        let mover = Hailstone::from_line("0, 0, 0 @ 2, 0, 0");
+        let still = Hailstone::from_line("3, 0, 0 @ 0, 0, 0");
+        assert_eq!(
+            mover.encounter(&still),
+            Some(Encounter::Collision { t: rational(3, 2) })
+        );
+        assert_eq!(
+            still.encounter(&mover),
+            Some(Encounter::Collision { t: rational(3, 2) })
+        );
+    }
+
+    #[test]
+    fn test_closest_approach() {
+        let eastbound = Hailstone::from_line("0, 0, 0 @ 1, 0, 0");
+        let westbound = Hailstone::from_line("10, 3, 0 @ -1, 0, 0");
+        assert_eq!(
+            eastbound.encounter(&westbound),
+            Some(Encounter::ClosestApproach {
+                t: rational(5, 1),
+                distance_squared: rational(9, 1)
+            })
+        );
+        //Already moving apart, so they are closest now.
+        let departing = Hailstone::from_line("-10, 3, 4 @ -1, 0, 0");
+        assert_eq!(
+            eastbound.encounter(&departing),
+            Some(Encounter::ClosestApproach {
+                t: rational(0, 1),
+                distance_squared: rational(125, 1)
+            })
+        );
+        //Skew paths, which are closest at a fractional time and distance.
+        let climbing = Hailstone::from_line("0, -1, 0 @ 0, 1, 2");
+        assert_eq!(
+            eastbound.encounter(&climbing),
+            Some(Encounter::ClosestApproach {
+                t: rational(1, 6),
+                distance_squared: rational(5, 6)
+            })
+        );
+        //The real input has positions in the hundreds of trillions.
+        let far = Hailstone::from_line(
+            "400000000000000, 400000000000000, 400000000000000 @ -999, 998, -997",
+        );
+        let near = Hailstone::from_line(
+            "200000000000000, 300000000000000, 100000000000000 @ 1000, -999, 998",
+        );
+        assert!(far.encounter(&near).is_some());
+    }
+
+    #[test]
+    fn test_describe_encounters() {
+        let hailstones = [
+            "0, 0, 0 @ 1, 0, 0",
+            "10, 3, 0 @ -1, 0, 0",
+            "-10, 3, 4 @ -1, 0, 0",
+            "3, 0, 0 @ 0, 0, 0",
+        ]
+        .map(Hailstone::from_line);
+        assert_eq!(
+            describe_encounters(&hailstones),
+            vec![
+                "Hailstones 1 and 2 have parallel paths",
+                "Hailstones 1 and 2 never collide, and come closest at t=5, with a squared distance of 9 (about 3.000)",
+                "Hailstones 1 and 3 have parallel paths",
+                "Hailstones 1 and 3 never collide, and come closest at t=0, with a squared distance of 125 (about 11.180)",
+                "Hailstones 1 and 4 collide at t=3 at (3, 0, 0)",
+                "Hailstones 2 and 3 have parallel paths",
+                "Hailstones 2 and 3 never collide, and come closest at t=0, with a squared distance of 416 (about 20.396)",
+                "Hailstones 2 and 4 never collide, and come closest at t=7, with a squared distance of 9 (about 3.000)",
+                "Hailstones 3 and 4 never collide, and come closest at t=0, with a squared distance of 194 (about 13.928)",
+                "Of the hailstones that never collide, 1 and 2 come closest at t=5, with a squared distance of 9 (about 3.000)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_alignment() {
+        let hailstone1 = Hailstone::from_line("18, 19, 22 @ -1, -1, -2");
+        let hailstone2 = Hailstone::from_line("20, 25, 34 @ -2, -2, -4");
+        assert_eq!(
+            hailstone1.path_alignment(&hailstone2),
+            Some(PathAlignment::Parallel)
+        );
+        let rock = Hailstone::from_line("24, 13, 10 @ -3, 1, 2");
+        assert_eq!(hailstone1.path_alignment(&rock), None);
+        let ahead = Hailstone::from_line("17, 18, 20 @ 3, 3, 6");
+        assert_eq!(
+            hailstone1.path_alignment(&ahead),
+            Some(PathAlignment::Collinear)
+        );
+        //Collinear paths heading towards each other collide.
+        assert_eq!(
+            hailstone1.encounter(&ahead),
+            Some(Encounter::Collision { t: rational(1, 4) })
+        );
+        let still = Hailstone::from_line("17, 18, 20 @ 0, 0, 0");
+        assert_eq!(hailstone1.path_alignment(&still), None);
+    }
 }
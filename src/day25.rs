@@ -1,38 +1,135 @@
 use crate::{read_input_file, SolveAdvent};
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
 
 pub struct Day25;
 
 impl SolveAdvent for Day25 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let wire_map = WireMap::from_input_file(path_to_file);
-        let unique_connection_permutations = (0..wire_map.connections.len()).combinations(3);
-
         println!(
-            "Wire map has {} connections and {} wire triplets to test",
-            wire_map.connections.len(),
-            unique_connection_permutations.clone().count()
+            "Wire map has {} components and {} connections",
+            wire_map.connections_map.len(),
+            wire_map.connections.len()
         );
-        for (index, connections_to_remove) in unique_connection_permutations.into_iter().enumerate()
-        {
-            if index % 1_000 == 0 {
-                println!("Checked {} potential triplets", index);
-            }
-            if let Some(perfect_partition) =
-                try_removing_connections(&wire_map, connections_to_remove)
-            {
-                println!("Got a perfect partition of size {}", perfect_partition);
-                break;
-            }
-        }
+        let partition = wire_map
+            .partition(2)
+            .unwrap_or_else(|err| panic!("{}", err));
+        partition.print();
+        let perfect_partition = partition
+            .groups
+            .iter()
+            .map(|group| group.len())
+            .product::<usize>();
+        println!("Got a perfect partition of size {}", perfect_partition);
+        perfect_partition.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let _ = path_to_file;
+        String::new()
     }
 }
 
+pub fn report_partition(path_to_file: &str, group_count: usize) -> Result<(), String> {
+    //! Print the wires to cut to split the components into `group_count` groups, and the size of each group.
+    let wire_map = WireMap::from_input_file(path_to_file);
+    wire_map.partition(group_count)?.print();
+    Ok(())
+}
+
+pub fn export_graph(
+    path_to_file: &str,
+    graph_path: &str,
+    graphml: bool,
+    group_count: usize,
+) -> Result<(), String> {
+    //! Write the wire map to `graph_path`, as GraphML if `graphml` is set and as Graphviz DOT otherwise.
+    //! The components are split into `group_count` groups, which are colored differently, and the
+    //! cut wires are drawn dashed.
+    let wire_map = WireMap::from_input_file(path_to_file);
+    let partition = wire_map.partition(group_count)?;
+    let graph = if graphml {
+        wire_map.to_graphml(&partition)
+    } else {
+        wire_map.to_dot(&partition)
+    };
+    fs::write(graph_path, graph)
+        .map_err(|err| format!("Could not write {}: {}", graph_path, err))?;
+    println!("Wrote the wire map to {}", graph_path);
+    Ok(())
+}
+
+///The colors of the groups of components in exported graphs, which repeat after the last.
+const GROUP_COLORS: [&str; 6] = ["lightblue", "orange", "palegreen", "pink", "khaki", "plum"];
+
+///A set of wires whose removal splits the components into two groups.
+#[derive(Debug, Clone, PartialEq)]
+struct WireCut {
+    ///The wires that are cut, as they appear in the input.
+    cut_connections: Vec<(String, String)>,
+    ///The components on the same side of the cut as the first component in sorted order.
+    group: HashSet<String>,
+}
+
+///The components split into groups by cutting wires.
+#[derive(Debug, Clone, PartialEq)]
+struct Partition {
+    ///The wires severed by each cut, in the order the cuts were made.
+    cuts: Vec<Vec<(String, String)>>,
+    ///The groups of components, largest first.
+    groups: Vec<HashSet<String>>,
+}
+
+impl Partition {
+    fn group_number(&self, component: &str) -> usize {
+        //! The index of the group the component is in.
+        self.groups
+            .iter()
+            .position(|group| group.contains(component))
+            .expect("Every component is in a group")
+    }
+
+    fn is_cut(&self, left_wire: &str, right_wire: &str) -> bool {
+        self.cuts.iter().flatten().any(|(cut_left, cut_right)| {
+            (cut_left == left_wire && cut_right == right_wire)
+                || (cut_left == right_wire && cut_right == left_wire)
+        })
+    }
+
+    fn print(&self) {
+        for (cut_number, cut_connections) in self.cuts.iter().enumerate() {
+            println!(
+                "Cut {} severs {} wires: {}",
+                cut_number + 1,
+                cut_connections.len(),
+                cut_connections
+                    .iter()
+                    .map(|(left_wire, right_wire)| format!("{}/{}", left_wire, right_wire))
+                    .join(", ")
+            );
+        }
+        println!(
+            "Cutting {} wires in total splits the components into {} groups of sizes {}",
+            self.cuts
+                .iter()
+                .map(|cut_connections| cut_connections.len())
+                .sum::<usize>(),
+            self.groups.len(),
+            self.groups.iter().map(|group| group.len()).join(", ")
+        );
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[derive(Debug, Clone)]
 
 struct WireMap {
@@ -42,10 +139,17 @@ struct WireMap {
 
 impl WireMap {
     fn from_input_file(file_path: &str) -> Self {
+        let file_as_string = read_input_file(file_path);
+        WireMap::new(&file_as_string)
+    }
+
+    fn new(file_as_string: &str) -> Self {
         let mut connections = Vec::new();
         let mut connections_map = HashMap::new();
-        let file_as_string = read_input_file(file_path);
-        for line in file_as_string.lines() {
+        for line in file_as_string
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+        {
             WireMap::parse_line(&mut connections_map, &mut connections, line);
         }
         WireMap {
@@ -54,6 +158,173 @@ impl WireMap {
         }
     }
 
+    fn component_names(&self) -> Vec<&String> {
+        //! Every component, in sorted order so that the results do not depend on hashing.
+        self.connections_map.keys().sorted().collect::<Vec<_>>()
+    }
+
+    fn global_min_cut(&self) -> Option<WireCut> {
+        //! The fewest wires that split the components into two groups, or None if there are fewer
+        //! than two components. Every cut separates the first component from some other component,
+        //! so the smallest of the cuts between the first component and each other one is the smallest overall.
+        let numbered_wires = NumberedWires::new(self);
+        let mut best_cut: Option<WireCut> = None;
+        for sink in 1..numbered_wires.component_names.len() {
+            //Only cuts smaller than the best so far are worth finding.
+            let max_cut_size = match &best_cut {
+                Some(best_cut) if best_cut.cut_connections.is_empty() => break,
+                Some(best_cut) => best_cut.cut_connections.len() - 1,
+                None => usize::MAX,
+            };
+            if let Some(wire_cut) = numbered_wires.min_cut_between(0, sink, max_cut_size) {
+                best_cut = Some(wire_cut);
+            }
+        }
+        best_cut
+    }
+
+    fn subgraph(&self, components: &HashSet<String>) -> WireMap {
+        //! The wire map with only the given components, and the wires between them.
+        let connections = self
+            .connections
+            .iter()
+            .filter(|(left_wire, right_wire)| {
+                components.contains(left_wire) && components.contains(right_wire)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let connections_map = components
+            .iter()
+            .map(|component| {
+                let neighbours = self.connections_map[component]
+                    .intersection(components)
+                    .cloned()
+                    .collect::<HashSet<_>>();
+                (component.clone(), neighbours)
+            })
+            .collect::<HashMap<_, _>>();
+        WireMap {
+            connections,
+            connections_map,
+        }
+    }
+
+    fn connected_groups(&self) -> Vec<HashSet<String>> {
+        //! The groups of components that are connected by wires, before any are cut.
+        let mut groups: Vec<HashSet<String>> = Vec::new();
+        for component in self.component_names() {
+            if groups.iter().any(|group| group.contains(component)) {
+                continue;
+            }
+            let mut group = HashSet::new();
+            let mut wire_stack = vec![component];
+            while let Some(current_wire) = wire_stack.pop() {
+                if group.insert(current_wire.clone()) {
+                    wire_stack.extend(self.connections_map[current_wire].iter());
+                }
+            }
+            groups.push(group);
+        }
+        groups
+    }
+
+    fn partition(&self, group_count: usize) -> Result<Partition, String> {
+        //! Split the components into at least `group_count` groups by cutting wires. Groups that are
+        //! not connected at all need no cuts. Until there are enough groups, the group with the smallest
+        //! minimum cut is split in two. For more than two groups this is greedy, so it may not cut
+        //! the fewest wires possible overall.
+        let mut groups = self.connected_groups();
+        let mut cuts = Vec::new();
+        while groups.len() < group_count {
+            let smallest_cut = groups
+                .iter()
+                .enumerate()
+                .filter_map(|(index, group)| Some((index, self.subgraph(group).global_min_cut()?)))
+                .min_by_key(|(_, wire_cut)| wire_cut.cut_connections.len());
+            let Some((index, wire_cut)) = smallest_cut else {
+                return Err(format!(
+                    "There are only {} components, which cannot be split into {} groups",
+                    self.connections_map.len(),
+                    group_count
+                ));
+            };
+            let (inside, outside) = groups
+                .swap_remove(index)
+                .into_iter()
+                .partition(|component| wire_cut.group.contains(component));
+            groups.push(inside);
+            groups.push(outside);
+            cuts.push(wire_cut.cut_connections);
+        }
+        groups.sort_by_key(|group| (Reverse(group.len()), group.iter().min().cloned()));
+        Ok(Partition { cuts, groups })
+    }
+
+    fn to_dot(&self, partition: &Partition) -> String {
+        //! The wire map as an undirected Graphviz DOT graph. Each group of components
+        //! is filled with its own color and the cut wires are red and dashed.
+        let mut dot = String::from("graph wires {\n    node [style=filled];\n");
+        for component in self.component_names() {
+            let color = GROUP_COLORS[partition.group_number(component) % GROUP_COLORS.len()];
+            dot.push_str(&format!(
+                "    \"{}\" [fillcolor={}];\n",
+                component.escape_default(),
+                color
+            ));
+        }
+        for (left_wire, right_wire) in self.connections.iter() {
+            let is_cut = partition.is_cut(left_wire, right_wire);
+            dot.push_str(&format!(
+                "    \"{}\" -- \"{}\"{};\n",
+                left_wire.escape_default(),
+                right_wire.escape_default(),
+                if is_cut {
+                    " [style=dashed, color=red]"
+                } else {
+                    ""
+                }
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn to_graphml(&self, partition: &Partition) -> String {
+        //! The wire map as an undirected GraphML graph. Each component records its group and color,
+        //! and each wire records whether it is cut, along with a dashed line style if it is.
+        let mut graphml = String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="group" for="node" attr.name="group" attr.type="int"/>
+  <key id="color" for="node" attr.name="color" attr.type="string"/>
+  <key id="cut" for="edge" attr.name="cut" attr.type="boolean"/>
+  <key id="style" for="edge" attr.name="style" attr.type="string"/>
+  <graph id="wires" edgedefault="undirected">
+"#,
+        );
+        for component in self.component_names() {
+            let group_number = partition.group_number(component);
+            graphml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"group\">{}</data><data key=\"color\">{}</data></node>\n",
+                xml_escape(component),
+                group_number,
+                GROUP_COLORS[group_number % GROUP_COLORS.len()]
+            ));
+        }
+        for (left_wire, right_wire) in self.connections.iter() {
+            let is_cut = partition.is_cut(left_wire, right_wire);
+            graphml.push_str(&format!(
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"cut\">{}</data><data key=\"style\">{}</data></edge>\n",
+                xml_escape(left_wire),
+                xml_escape(right_wire),
+                is_cut,
+                if is_cut { "dashed" } else { "solid" }
+            ));
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
     fn parse_line(
         connections_map: &mut HashMap<String, HashSet<String>>,
         connections: &mut Vec<(String, String)>,
@@ -86,70 +357,261 @@ impl WireMap {
     }
 }
 
-fn try_removing_connections(
-    wire_map: &WireMap,
-    connections_to_remove: Vec<usize>,
-) -> Option<usize> {
-    //! Given the 3 wires to remove from the wire map, create a copy of the `connections_map` but with
-    //! those three connections removed.
-    let mut connection_map_probe = wire_map.connections_map.clone();
-    let connections_to_remove = connections_to_remove
-        .into_iter()
-        .map(|connection_to_remove| wire_map.connections.get(connection_to_remove).unwrap())
-        .collect::<Vec<_>>();
-    //For the 3 connections chosen to be removed, remove them from the Wire Map.
-    for (left_wire, right_wire) in connections_to_remove {
-        connection_map_probe
-            .get_mut(left_wire)
-            .unwrap()
-            .remove(right_wire);
-        connection_map_probe
-            .get_mut(right_wire)
-            .unwrap()
-            .remove(left_wire);
+///The wire map with every component numbered, in sorted order, so that cuts can be found
+/// without looking up names.
+struct NumberedWires<'a> {
+    wire_map: &'a WireMap,
+    component_names: Vec<&'a String>,
+    ///The components at each end of every wire, in the same order as `connections`.
+    wires: Vec<(usize, usize)>,
+    ///The wires attached to each component.
+    wires_at: Vec<Vec<usize>>,
+}
+
+impl<'a> NumberedWires<'a> {
+    fn new(wire_map: &'a WireMap) -> NumberedWires<'a> {
+        let component_names = wire_map.component_names();
+        let index_of = component_names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let wires = wire_map
+            .connections
+            .iter()
+            .map(|(left_wire, right_wire)| {
+                (index_of[left_wire.as_str()], index_of[right_wire.as_str()])
+            })
+            .collect::<Vec<_>>();
+        let mut wires_at = vec![Vec::new(); component_names.len()];
+        for (wire, &(left, right)) in wires.iter().enumerate() {
+            wires_at[left].push(wire);
+            wires_at[right].push(wire);
+        }
+        NumberedWires {
+            wire_map,
+            component_names,
+            wires,
+            wires_at,
+        }
     }
-    //Make sure that all keys mapped to an empty set are removed.
-    connection_map_probe.retain(|_k, v| !v.is_empty());
 
-    let mut partition_group_sizes = Vec::new();
-    while !connection_map_probe.is_empty() {
-        if partition_group_sizes.len() > 2 {
-            //If the partition group size is greater than 2, then the
-            //3 wires remove do not split the wires into 2 groups.
-            return None;
+    fn min_cut_between(&self, source: usize, sink: usize, max_cut_size: usize) -> Option<WireCut> {
+        //! The fewest wires that separate `source` from `sink`, or None if more than
+        //! `max_cut_size` wires are needed.
+        //!
+        //! By Menger's theorem, this is the number of paths between them that share no wires, which
+        //! are found one at a time with a breadth first search, as in the Edmonds-Karp algorithm.
+        //! Once no more paths can be found, the components the search still reaches form one side of the cut.
+        let (component_names, wires, wires_at) =
+            (&self.component_names, &self.wires, &self.wires_at);
+        //Each wire is used by at most one path, in one direction. `flow[wire]` is 1 if it is used
+        //from left to right, -1 if from right to left, and 0 if it is not used.
+        let mut flow = vec![0_i8; wires.len()];
+
+        let mut path_count = 0;
+        loop {
+            //Search for a path using only wires with spare capacity in the direction travelled.
+            let mut arrived_by = vec![None; component_names.len()];
+            let mut reached = vec![false; component_names.len()];
+            reached[source] = true;
+            let mut queue = VecDeque::from([source]);
+            while let Some(component) = queue.pop_front() {
+                for &wire in wires_at[component].iter() {
+                    let (left, right) = wires[wire];
+                    let (next, direction) = if left == component {
+                        (right, 1)
+                    } else {
+                        (left, -1)
+                    };
+                    if !reached[next] && flow[wire] != direction {
+                        reached[next] = true;
+                        arrived_by[next] = Some(wire);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            if !reached[sink] {
+                let group = (0..component_names.len())
+                    .filter(|&component| reached[component])
+                    .map(|component| component_names[component].clone())
+                    .collect::<HashSet<_>>();
+                let cut_connections = self
+                    .wire_map
+                    .connections
+                    .iter()
+                    .zip(wires.iter())
+                    .filter(|(_, &(left, right))| reached[left] != reached[right])
+                    .map(|(connection, _)| connection.clone())
+                    .collect::<Vec<_>>();
+                return Some(WireCut {
+                    cut_connections,
+                    group,
+                });
+            }
+            if path_count == max_cut_size {
+                return None;
+            }
+            //Walk back along the path, using each wire in the direction travelled.
+            let mut component = sink;
+            while let Some(wire) = arrived_by[component] {
+                let (left, right) = wires[wire];
+                if right == component {
+                    flow[wire] += 1;
+                    component = left;
+                } else {
+                    flow[wire] -= 1;
+                    component = right;
+                }
+            }
+            path_count += 1;
         }
-        let visited_wires = explore_wire(&connection_map_probe);
-        partition_group_sizes.push(visited_wires.len());
-        connection_map_probe.retain(|key, _value| !visited_wires.contains(key));
     }
-    if partition_group_sizes.len() == 2 {
-        println!(
-            "Got a division that created 2 groups with contents {:?}",
-            partition_group_sizes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    fn group_sizes(partition: &Partition) -> Vec<usize> {
+        partition
+            .groups
+            .iter()
+            .map(|group| group.len())
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn test_global_min_cut() {
+        let wire_map = WireMap::new(EXAMPLE_INPUT);
+        let wire_cut = wire_map.global_min_cut().unwrap();
+        let cut_connections = wire_cut
+            .cut_connections
+            .iter()
+            .map(|(left_wire, right_wire)| format!("{}/{}", left_wire, right_wire))
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(cut_connections, vec!["cmg/bvb", "jqt/nvd", "pzl/hfx"]);
+        let group_sizes = (wire_cut.group.len(), 15 - wire_cut.group.len());
+        assert!(group_sizes == (9, 6) || group_sizes == (6, 9));
+        let numbered_wires = NumberedWires::new(&wire_map);
+        let number_of = |name: &str| {
+            numbered_wires
+                .component_names
+                .iter()
+                .position(|component| *component == name)
+                .unwrap()
+        };
+        assert_eq!(
+            numbered_wires
+                .min_cut_between(number_of("bvb"), number_of("cmg"), 3)
+                .unwrap()
+                .cut_connections
+                .len(),
+            3
+        );
+        assert_eq!(
+            numbered_wires.min_cut_between(number_of("bvb"), number_of("hfx"), 3),
+            None
+        );
+        //Two groups of four, where each component is wired to every other one in its group,
+        //joined by two wires.
+        let wire_map = WireMap::new(
+            "a: b c d
+b: c d
+c: d
+w: x y z
+x: y z
+y: z
+a: w
+b: x",
+        );
+        let wire_cut = wire_map.global_min_cut().unwrap();
+        assert_eq!(wire_cut.cut_connections.len(), 2);
+        assert_eq!(wire_cut.group.len(), 4);
+        let single_component = HashSet::from(["a".to_owned()]);
+        assert_eq!(
+            WireMap::new("a: b")
+                .subgraph(&single_component)
+                .global_min_cut(),
+            None
         );
-        return Some(partition_group_sizes[0] * partition_group_sizes[1]);
     }
-    None
-}
 
-fn explore_wire(connection_map_probe: &HashMap<String, HashSet<String>>) -> HashSet<String> {
-    //! Given the wire map (with already visited wires removed), traverse the wire until traversal
-    //! is no longer possible without a cycle. Return the set of unique wires that can be traversed from the
-    //! given first_connection.
-    let first_connection = connection_map_probe
-        .keys()
-        .next()
-        .expect("Connection Map was empty");
-
-    let mut wire_stack = vec![first_connection];
-    let mut visited_tracker = HashSet::new();
-    while let Some(current_wire) = wire_stack.pop() {
-        if !visited_tracker.contains(current_wire) {
-            visited_tracker.insert(current_wire.clone());
-            for next_wire in connection_map_probe.get(current_wire).unwrap() {
-                wire_stack.push(next_wire);
-            }
+    #[test]
+    fn test_partition() {
+        let wire_map = WireMap::new(EXAMPLE_INPUT);
+        let partition = wire_map.partition(2).unwrap();
+        assert_eq!(group_sizes(&partition), vec![9, 6]);
+        assert_eq!(partition.cuts.len(), 1);
+        assert_eq!(partition.cuts[0].len(), 3);
+        //Every wire between two groups is cut, and no other.
+        for group_count in 3..=6 {
+            let partition = wire_map.partition(group_count).unwrap();
+            assert_eq!(partition.groups.len(), group_count);
+            assert_eq!(group_sizes(&partition).iter().sum::<usize>(), 15);
+            let wires_between_groups = wire_map
+                .connections
+                .iter()
+                .filter(|(left_wire, right_wire)| {
+                    partition.group_number(left_wire) != partition.group_number(right_wire)
+                })
+                .count();
+            assert_eq!(
+                wires_between_groups,
+                partition.cuts.iter().flatten().count()
+            );
         }
+        //Groups that are not wired together need no cuts.
+        let wire_map = WireMap::new("a: b\nc: d e\nd: e");
+        let partition = wire_map.partition(2).unwrap();
+        assert_eq!(group_sizes(&partition), vec![3, 2]);
+        assert!(partition.cuts.is_empty());
+        let partition = wire_map.partition(3).unwrap();
+        assert_eq!(group_sizes(&partition), vec![3, 1, 1]);
+        assert_eq!(partition.cuts, vec![vec![("a".to_owned(), "b".to_owned())]]);
+        assert_eq!(wire_map.partition(5).unwrap().groups.len(), 5);
+        assert!(wire_map.partition(6).is_err());
+    }
+
+    #[test]
+    fn test_exports() {
+        let wire_map = WireMap::new(EXAMPLE_INPUT);
+        let partition = wire_map.partition(2).unwrap();
+        let dot = wire_map.to_dot(&partition);
+        assert!(dot.starts_with("graph wires {\n"));
+        assert_eq!(dot.matches(" -- ").count(), 33);
+        assert_eq!(dot.matches("style=dashed").count(), 3);
+        assert!(dot.contains("    \"pzl\" -- \"hfx\" [style=dashed, color=red];\n"));
+        assert_eq!(dot.matches("fillcolor=lightblue").count(), 9);
+        assert_eq!(dot.matches("fillcolor=orange").count(), 6);
+
+        let graphml = wire_map.to_graphml(&partition);
+        assert_eq!(graphml.matches("<node ").count(), 15);
+        assert_eq!(graphml.matches("<edge ").count(), 33);
+        assert_eq!(graphml.matches("<data key=\"cut\">true</data>").count(), 3);
+        assert!(graphml.contains(
+            "<edge source=\"jqt\" target=\"nvd\"><data key=\"cut\">true</data><data key=\"style\">dashed</data></edge>"
+        ));
+        assert!(graphml.ends_with("  </graph>\n</graphml>\n"));
+        assert_eq!(graphml.matches("<data key=\"group\">0</data>").count(), 9);
+        //Without a cut, everything is in one group.
+        let graphml = wire_map.to_graphml(&wire_map.partition(1).unwrap());
+        assert_eq!(graphml.matches("<data key=\"group\">0</data>").count(), 15);
+        assert_eq!(graphml.matches("dashed").count(), 0);
     }
-    visited_tracker
 }
@@ -4,7 +4,7 @@ use std::collections::{HashMap, HashSet};
 pub struct Day4;
 
 impl SolveAdvent for Day4 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let mut total_score_of_cards = 0;
         for line in file_as_str.lines() {
@@ -19,9 +19,10 @@ impl SolveAdvent for Day4 {
             total_score_of_cards += score_card(winning_card_count);
         }
         println!("Total Cards Score: {}", total_score_of_cards);
+        total_score_of_cards.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         //The card_counter HashMap stores how many of each card you have won. Intialize it to all to 1's
         let mut card_counter = HashMap::new();
@@ -48,6 +49,7 @@ impl SolveAdvent for Day4 {
         }
         let sum_of_cards: i32 = card_counter.values().copied().sum();
         println!("Total Card Counts including duplicates: {}", sum_of_cards);
+        sum_of_cards.to_string()
     }
 }
 
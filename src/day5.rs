@@ -1,114 +1,268 @@
 use crate::{read_input_file, SolveAdvent};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct Day5;
 
 impl SolveAdvent for Day5 {
-    fn solve_part1(path_to_file: &str) {
-        let (seeds, maps_hashmap) = process_into_maps(path_to_file);
-        let processed_maps = process_maps_into_numbers(maps_hashmap);
+    fn solve_part1(path_to_file: &str) -> String {
+        let almanac =
+            Almanac::from_input_file(path_to_file).unwrap_or_else(|err| panic!("{}", err));
+        let seed_to_location = almanac
+            .chain("seed", "location")
+            .unwrap_or_else(|err| panic!("{}", err));
         let mut min_seen = usize::MAX;
-        for seed_input in seeds {
-            let location_value = apply_chain(seed_input, &processed_maps);
+        for seed_input in almanac.seeds {
+            let location_value = seed_to_location.apply(seed_input);
             if location_value < min_seen {
                 min_seen = location_value;
             }
         }
 
         println!("Minimum location value for part1 is {}", min_seen);
+        min_seen.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
-        let (seeds, maps_hashmap) = process_into_maps(path_to_file);
-        let processed_maps = process_maps_into_numbers(maps_hashmap);
-        let minimum_location = find_location_using_ranges(seeds, &processed_maps);
+    fn solve_part2(path_to_file: &str) -> String {
+        let almanac =
+            Almanac::from_input_file(path_to_file).unwrap_or_else(|err| panic!("{}", err));
+        let seed_to_location = almanac
+            .chain("seed", "location")
+            .unwrap_or_else(|err| panic!("{}", err));
+        let minimum_location = find_location_using_ranges(almanac.seeds, &seed_to_location);
         println!("Minimum location value for part2 is {}", minimum_location);
+        if let Some(location_to_seed) = seed_to_location.invert() {
+            println!(
+                "It is reached from seed {}",
+                location_to_seed.apply(minimum_location)
+            );
+        }
+        minimum_location.to_string()
     }
 }
 
-fn process_into_maps(fp: &str) -> (Vec<usize>, HashMap<String, String>) {
-    //! Given the input fp, build a seeds vec (the inputs to the chain)
-    //! and a HashMap of the map name to the maps items as a string.
-    let input_str = read_input_file(fp);
-    let mut final_string = String::new();
-    let mut almanac_maps = HashMap::new();
-    //For easier parsing, replace the empty lines with '|'.
-    for line in input_str.lines() {
-        if !line.is_empty() {
-            final_string.push_str(line);
-            final_string.push('\n');
-        } else {
-            final_string.push('|');
+pub fn explain(path_to_file: &str, part: usize) -> Result<(), String> {
+    //! Find the seed that reaches the minimum location, and print its path through every map.
+    //! Part1 treats the seeds as single values, and part2 treats them as ranges.
+    let almanac = Almanac::from_input_file(path_to_file)?;
+    let seed_to_location = almanac.chain("seed", "location")?;
+    let winner = if part == 1 {
+        almanac
+            .seeds
+            .iter()
+            .map(|&seed| (seed, seed_to_location.apply(seed)))
+            .min_by_key(|&(_, location)| location)
+    } else {
+        let seed_ranges = generate_seed_ranges(almanac.seeds.clone())
+            .into_iter()
+            .map(|(lower, upper)| Range::new(lower, upper))
+            .collect::<Vec<_>>();
+        seed_to_location.minimum_over_ranges(&seed_ranges)
+    };
+    let Some((seed, location)) = winner else {
+        println!("There were no seeds");
+        return Ok(());
+    };
+
+    println!(
+        "Seed {} reaches the minimum location {} for part{}",
+        seed, location, part
+    );
+    for step in almanac.trace(seed, "seed", "location")? {
+        match step.fired_line {
+            Some((dest_range_start, src_range_start, range)) => println!(
+                "    {} {} -> {} {} using line `{} {} {}`",
+                step.category,
+                step.value,
+                step.next_category,
+                step.next_value,
+                dest_range_start,
+                src_range_start,
+                range
+            ),
+            None => println!(
+                "    {} {} -> {} {} as no line covers it",
+                step.category, step.value, step.next_category, step.next_value
+            ),
         }
     }
-    for partition in final_string.split('|') {
-        let mut partition_splitter = partition.split(':');
-        let name = partition_splitter.next().unwrap().trim();
-        let map = partition_splitter.next().unwrap().trim();
-        almanac_maps.insert(name.to_owned(), map.to_owned());
+    Ok(())
+}
+
+///A single `X-to-Y map` section of the input.
+#[derive(Debug, Clone)]
+struct CategoryMap {
+    ///The category this maps to, the Y in `X-to-Y`.
+    destination: String,
+    ///The `(dest_start, src_start, range)` lines, in the order of the input.
+    mapping_lines: Vec<(usize, usize, usize)>,
+    map: PiecewiseMap,
+}
+
+impl CategoryMap {
+    fn fired_line(&self, value: usize) -> Option<(usize, usize, usize)> {
+        //! The mapping line that moves the value, which is the first line covering it.
+        self.mapping_lines
+            .iter()
+            .find(|(_, src_range_start, range)| {
+                (*src_range_start..src_range_start + range).contains(&value)
+            })
+            .copied()
     }
+}
+
+///One stage of a value's path through the maps.
+#[derive(Debug, Clone, PartialEq)]
+struct TraceStep {
+    category: String,
+    value: usize,
+    next_category: String,
+    next_value: usize,
+    ///The mapping line responsible for the move, or None if the value maps to itself.
+    fired_line: Option<(usize, usize, usize)>,
+}
 
-    let seeds = almanac_maps.remove("seeds").unwrap();
-    let seeds = seeds
-        .split(' ')
-        .map(|item| item.parse::<usize>().unwrap())
-        .collect::<Vec<usize>>();
-    (seeds, almanac_maps)
+///The parsed input file. Each map is stored under the category it maps from.
+#[derive(Debug, Clone)]
+struct Almanac {
+    seeds: Vec<usize>,
+    maps: HashMap<String, CategoryMap>,
 }
 
-fn process_maps_into_numbers(
-    almanac_map: HashMap<String, String>,
-) -> HashMap<String, Vec<(usize, usize, usize)>> {
-    //! Given the almanac_map, process the strings into vecs of numbers.
-    let mut processed_alamanac_map = HashMap::new();
-
-    for (map_name, map) in almanac_map.into_iter() {
-        let mut range_vec = Vec::new();
-        for line in map.lines() {
-            let mut line_iterator = line
-                .split(' ')
-                .map(|item| item.trim().parse::<usize>().unwrap());
-            let dest_range_start = line_iterator.next().unwrap();
-            let src_range_start = line_iterator.next().unwrap();
-            let range = line_iterator.next().unwrap();
-            range_vec.push((src_range_start, range, dest_range_start));
+impl Almanac {
+    fn from_input_file(fp: &str) -> Result<Almanac, String> {
+        //! Given the input fp, build the seeds vec (the inputs to the chain)
+        //! and a `PiecewiseMap` for each of the `X-to-Y map` sections.
+        let input_str = read_input_file(fp);
+        Almanac::new(&input_str)
+    }
+
+    fn new(input_str: &str) -> Result<Almanac, String> {
+        //Sections are separated by blank lines. Going line by line, rather than splitting on
+        //`\n\n`, means input with `\r\n` line endings is split the same way.
+        let mut sections = Vec::new();
+        let mut section = Vec::new();
+        for line in input_str.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                if !section.is_empty() {
+                    sections.push(std::mem::take(&mut section));
+                }
+            } else {
+                section.push(line);
+            }
+        }
+        if !section.is_empty() {
+            sections.push(section);
+        }
+        let mut sections = sections.into_iter();
+
+        let seeds_line = sections
+            .next()
+            .map(|section| section.join(" "))
+            .ok_or_else(|| "The almanac is empty".to_owned())?;
+        let seeds = seeds_line
+            .strip_prefix("seeds:")
+            .ok_or_else(|| format!("Expected `seeds: ...`, got `{}`", seeds_line))?
+            .split_whitespace()
+            .map(|item| {
+                item.parse::<usize>()
+                    .map_err(|_| format!("Invalid seed `{}`", item))
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let mut maps = HashMap::new();
+        for section in sections {
+            //The section header looks like `seed-to-soil map:`
+            let header = section[0];
+            let (source, destination) =
+                header
+                    .trim_end_matches("map:")
+                    .trim()
+                    .split_once("-to-")
+                    .ok_or_else(|| format!("Map header `{}` is not of the form X-to-Y", header))?;
+            let mapping_lines = section[1..]
+                .iter()
+                .map(|line| {
+                    let numbers = line
+                        .split_whitespace()
+                        .map(|item| item.parse::<usize>())
+                        .collect::<Result<Vec<_>, _>>();
+                    match numbers.as_deref() {
+                        Ok(&[dest_range_start, src_range_start, range]) => {
+                            Ok((dest_range_start, src_range_start, range))
+                        }
+                        _ => Err(format!(
+                            "Expected three numbers in the {} map, got `{}`",
+                            header, line
+                        )),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            maps.insert(
+                source.to_owned(),
+                CategoryMap {
+                    destination: destination.to_owned(),
+                    map: PiecewiseMap::from_mapping_lines(&mapping_lines),
+                    mapping_lines,
+                },
+            );
         }
-        processed_alamanac_map.insert(map_name, range_vec);
+        Ok(Almanac { seeds, maps })
     }
-    processed_alamanac_map
-}
 
-fn get_single_map_value(
-    map_key: &str,
-    input_int: usize,
-    almanac_maps: &HashMap<String, Vec<(usize, usize, usize)>>,
-) -> usize {
-    //! Given the desired map to use, the input integer, and the almanac,
-    //! use the rules to return the correct value.
-    let range_iterator = almanac_maps.get(map_key).unwrap();
-    for (src_start, range, dest_start) in range_iterator {
-        //Iterate over each item in the map
-        if &input_int >= src_start && input_int < src_start + range {
-            //If the input falls in the input range, then the correct return value
-            //is the dest_start plus how far from the src_start the input_int is.
-            let delta = input_int - src_start;
-            return dest_start + delta;
+    fn chain(&self, from: &str, to: &str) -> Result<PiecewiseMap, String> {
+        //! Follow the `X-to-Y` maps from the `from` category until the `to` category is
+        //! reached, composing every map along the way into a single `PiecewiseMap`.
+        let mut category = from;
+        let mut visited = HashSet::new();
+        let mut composed_map = PiecewiseMap::identity();
+        while category != to {
+            let category_map = self.next_map(category, to, &mut visited)?;
+            composed_map = composed_map.then(&category_map.map);
+            category = &category_map.destination;
         }
+        Ok(composed_map)
     }
-    //If its not in the map, then the correct value is the input_id
-    input_int
-}
 
-fn apply_chain(seed_input: usize, maps: &HashMap<String, Vec<(usize, usize, usize)>>) -> usize {
-    // ! Given the seed_input, apply the chain of maps, returning the location_value.
-    let soil_value = get_single_map_value("seed-to-soil map", seed_input, maps);
-    let fertilizer_value = get_single_map_value("soil-to-fertilizer map", soil_value, maps);
-    let water_value = get_single_map_value("fertilizer-to-water map", fertilizer_value, maps);
-    let light_value = get_single_map_value("water-to-light map", water_value, maps);
-    let temperature_value = get_single_map_value("light-to-temperature map", light_value, maps);
-    let humidity_value =
-        get_single_map_value("temperature-to-humidity map", temperature_value, maps);
-    get_single_map_value("humidity-to-location map", humidity_value, maps)
+    fn trace(&self, value: usize, from: &str, to: &str) -> Result<Vec<TraceStep>, String> {
+        //! Follow the value through each map in turn, from the `from` category to the `to` category.
+        let mut category = from;
+        let mut visited = HashSet::new();
+        let mut value = value;
+        let mut steps = Vec::new();
+        while category != to {
+            let category_map = self.next_map(category, to, &mut visited)?;
+            let next_value = category_map.map.apply(value);
+            steps.push(TraceStep {
+                category: category.to_owned(),
+                value,
+                next_category: category_map.destination.clone(),
+                next_value,
+                fired_line: category_map.fired_line(value),
+            });
+            category = &category_map.destination;
+            value = next_value;
+        }
+        Ok(steps)
+    }
+
+    fn next_map<'a>(
+        &'a self,
+        category: &'a str,
+        to: &str,
+        visited: &mut HashSet<&'a str>,
+    ) -> Result<&'a CategoryMap, String> {
+        //! The map out of `category`, which is recorded in `visited`. Reaching a category
+        //! twice means the maps go round in a cycle that never reaches `to`.
+        if !visited.insert(category) {
+            return Err(format!(
+                "The maps loop back to {} without reaching {}",
+                category, to
+            ));
+        }
+        self.maps
+            .get(category)
+            .ok_or_else(|| format!("No map leads from {} towards {}", category, to))
+    }
 }
 
 fn generate_seed_ranges(mut seed_ranges: Vec<usize>) -> Vec<(usize, usize)> {
@@ -127,72 +281,20 @@ fn generate_seed_ranges(mut seed_ranges: Vec<usize>) -> Vec<(usize, usize)> {
     seed_ranges
 }
 
-fn find_location_using_ranges(
-    seed_ranges: Vec<usize>,
-    maps: &HashMap<String, Vec<(usize, usize, usize)>>,
-) -> usize {
-    //! Process all of the ranges from each of the maps one after the other.
-    //! If done correctly, the final map maps the range of seeds to the range
-    //! of locations, which allows for trivial computation of the minimum possible
-    //! location.
-    let map_names = [
-        "seed-to-soil map",
-        "soil-to-fertilizer map",
-        "fertilizer-to-water map",
-        "water-to-light map",
-        "light-to-temperature map",
-        "temperature-to-humidity map",
-        "humidity-to-location map",
-    ];
-
-    let paired_seeds = generate_seed_ranges(seed_ranges);
-    let mut min_seen = usize::MAX;
-
-    //Initialize the current_range_map to contain a mapping of the seed intervals to themselves.
-    //Each iteration of the outermost for loop changes what the current_range_map maps. The keys are always
-    //the seed ranges, but the values become the ranges of the next map once applied.
-    //So after the first loop, the current_range_map changes from a map of seeds to seed to a map of
-    //seeds to soil.
-    let mut current_range_map = paired_seeds
+fn find_location_using_ranges(seed_ranges: Vec<usize>, seed_to_location: &PiecewiseMap) -> usize {
+    //! Map all of the seed ranges through the composed seed-to-location map at once.
+    //! The output is the set of all location ranges, which allows for trivial computation
+    //! of the minimum possible location.
+    let seed_ranges = generate_seed_ranges(seed_ranges)
         .into_iter()
-        .map(|pair| (pair, pair))
-        .collect::<HashMap<(usize, usize), (usize, usize)>>();
-    for map_key in map_names.iter() {
-        //Drain the current map
-        let drained_current_map = current_range_map.drain().collect::<Vec<_>>();
-        for (current_src, current_dest) in drained_current_map.iter() {
-            let current_src_range = Range::new(current_src.0, current_src.1);
-            let current_dest_range = Range::new(current_dest.0, current_dest.1);
-            let mut next_collected_map_src = Vec::new();
-            for (src_start, range, dest_start) in maps.get(*map_key).unwrap() {
-                let next_src_range = Range::new(*src_start, src_start + range - 1);
-                let next_dest_range = Range::new(*dest_start, dest_start + range - 1);
-                current_range_map.extend(apply_range_mapping(
-                    current_src_range.clone(),
-                    current_dest_range.clone(),
-                    next_src_range.clone(),
-                    next_dest_range,
-                ));
-                //Store the next_src_range's for later.
-                next_collected_map_src.push(next_src_range);
-            }
-
-            current_range_map.extend(fill_unmapped_ranges(
-                current_src_range,
-                current_dest_range,
-                next_collected_map_src,
-            ));
-        }
-    }
-    //The lowest number in the values of the current_range_map are the minimum
-    //possible location.
-    for (lower_location, _) in current_range_map.values() {
-        if lower_location < &min_seen {
-            min_seen = *lower_location;
-        }
-    }
-
-    min_seen
+        .map(|(lower, upper)| Range::new(lower, upper))
+        .collect::<Vec<_>>();
+    seed_to_location
+        .apply_to_ranges(&seed_ranges)
+        .into_iter()
+        .map(|location_range| location_range.lower as usize)
+        .min()
+        .expect("There were no seeds")
 }
 
 ///Simple Range type to help with reasoning
@@ -217,9 +319,24 @@ impl Range {
         self.lower.max(other.lower) <= self.upper.min(other.upper)
     }
 
-    fn into_ordered_pair(self) -> (usize, usize) {
-        //! Convert the Range type back into a tuple.
-        (self.lower as usize, self.upper as usize)
+    fn intersection(&self, other: &Range) -> Option<Range> {
+        //! The values that are in both ranges, if there are any.
+        let intersection = Range {
+            lower: self.lower.max(other.lower),
+            upper: self.upper.min(other.upper),
+        };
+        if !intersection.is_valid_range() {
+            return None;
+        }
+        Some(intersection)
+    }
+
+    fn shifted(&self, offset: isize) -> Range {
+        //! Move both ends of the range by the offset.
+        Range {
+            lower: self.lower + offset,
+            upper: self.upper + offset,
+        }
     }
 
     fn is_valid_range(&self) -> bool {
@@ -229,197 +346,477 @@ impl Range {
     }
 }
 
-fn apply_range_mapping(
-    current_map_src: Range,
-    current_map_dest: Range,
-    next_map_src: Range,
-    next_map_dest: Range,
-) -> HashMap<(usize, usize), (usize, usize)> {
-    //! Apply the basic merge operation of the old range map to the new range map.
-    //! For example, suppose the current_map was (15,22) -> (36, 43) and the next_map
-    //! was (30-37) -> (100-107). The output should be:
-    //! (21, 22) -> (106, 107). Note that the src will always be a subset of the original src, but the
-    //! dest has likely changed completely. Importantly, the requirement that unmapped ranges return themselves
-    //! cannot be handled here, because each call of this function does not know if the next map_src will match.
-    let mut outbound_map = HashMap::with_capacity(1);
-    let merged_map = Range {
-        lower: current_map_dest.lower.max(next_map_src.lower),
-        upper: current_map_dest.upper.min(next_map_src.upper),
-    };
-    if !merged_map.is_valid_range() {
-        return outbound_map;
+fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
+    //! Sort the ranges, merging any that overlap or touch.
+    ranges.sort_by_key(|range| range.lower);
+    let mut merged_ranges: Vec<Range> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged_ranges.last_mut() {
+            Some(last_range) if range.lower <= last_range.upper + 1 => {
+                last_range.upper = last_range.upper.max(range.upper);
+            }
+            _ => merged_ranges.push(range),
+        }
     }
-    let new_src_range = Range {
-        lower: current_map_src.lower + merged_map.lower - current_map_dest.lower,
-        upper: current_map_src.upper + merged_map.upper - current_map_dest.upper,
-    };
-    let new_dest_range = Range {
-        lower: next_map_dest.lower + merged_map.lower - next_map_src.lower,
-        upper: next_map_dest.upper + merged_map.upper - next_map_src.upper,
-    };
-    outbound_map.insert(
-        new_src_range.into_ordered_pair(),
-        new_dest_range.into_ordered_pair(),
-    );
-    outbound_map
+    merged_ranges
 }
 
-fn fill_unmapped_ranges(
-    current_src_range: Range,
-    current_dest_range: Range,
-    next_src_ranges: Vec<Range>,
-) -> HashMap<(usize, usize), (usize, usize)> {
-    //! The problem description requires that unmapped ranges be mapped to themselves.
-    //! This function accomplishes this by starting with a single hole representing the entire
-    //! destination range. Iterating over the next_src_ranges, the ranges known to not be holes
-    //! are removed. When the iteration is complete, any remaining holes are real holes, that need
-    //! to be mapped to themselves.
-    let mut potential_holes = vec![current_dest_range.clone()];
-    for next_src_range in next_src_ranges {
-        let mut counter = 0;
-        while counter < potential_holes.len() {
-            let current_hole = potential_holes.get(counter).unwrap();
-            if current_hole.overlaps(&next_src_range) {
-                let new_holes = [
-                    Range {
-                        lower: current_hole.lower.min(next_src_range.lower),
-                        upper: current_hole.lower.max(next_src_range.lower) - 1,
-                    },
-                    Range {
-                        lower: current_hole.upper.min(next_src_range.upper) + 1,
-                        upper: current_hole.upper.max(next_src_range.upper),
-                    },
-                ];
+///The largest value a `PiecewiseMap` knows about. Everything from 0 up to
+/// this value is part of the domain.
+const DOMAIN_UPPER: isize = isize::MAX / 2;
+
+///A single piece of a `PiecewiseMap`: every value in `source` is
+/// mapped to itself plus `offset`.
+#[derive(Clone, Debug, PartialEq)]
+struct MapSegment {
+    source: Range,
+    offset: isize,
+}
+
+impl MapSegment {
+    fn image(&self) -> Range {
+        //! The range of values this segment maps to.
+        self.source.shifted(self.offset)
+    }
+}
 
-                let new_holes = new_holes
+///A piecewise linear function, such as a single almanac map or a chain of them.
+/// Values that no segment covers map to themselves, as the problem describes.
+#[derive(Clone, Debug, PartialEq)]
+struct PiecewiseMap {
+    ///Non-overlapping segments sorted by the start of their source range.
+    /// Segments that would map values to themselves are never stored.
+    segments: Vec<MapSegment>,
+}
+
+impl PiecewiseMap {
+    fn identity() -> PiecewiseMap {
+        //! The map that sends every value to itself.
+        PiecewiseMap {
+            segments: Vec::new(),
+        }
+    }
+
+    fn from_mapping_lines(mapping_lines: &[(usize, usize, usize)]) -> PiecewiseMap {
+        //! Build the map from the `(dest_start, src_start, range)` lines of an almanac map.
+        //! Where lines overlap, the earlier line wins, as it is the first one a lookup would find.
+        let mut segments: Vec<MapSegment> = Vec::new();
+        for &(dest_start, src_start, range) in mapping_lines {
+            if range == 0 {
+                continue;
+            }
+            let mut uncovered = vec![Range::new(src_start, src_start + range - 1)];
+            for segment in segments.iter() {
+                uncovered = uncovered
                     .into_iter()
-                    .filter(|range| range.overlaps(current_hole))
+                    .flat_map(|piece| subtract_range(&piece, &segment.source))
                     .collect::<Vec<_>>();
-                potential_holes.remove(counter);
-                if !new_holes.is_empty() {
-                    potential_holes.extend(new_holes);
-                    break;
+            }
+            let offset = dest_start as isize - src_start as isize;
+            segments.extend(
+                uncovered
+                    .into_iter()
+                    .map(|source| MapSegment { source, offset }),
+            );
+        }
+        PiecewiseMap::from_segments(segments)
+    }
+
+    fn from_segments(mut segments: Vec<MapSegment>) -> PiecewiseMap {
+        //! Normalize the non-overlapping segments: drop the ones that map values to
+        //! themselves, and merge neighbours that share an offset.
+        segments.retain(|segment| segment.offset != 0);
+        segments.sort_by_key(|segment| segment.source.lower);
+        let mut merged_segments: Vec<MapSegment> = Vec::with_capacity(segments.len());
+        for segment in segments {
+            match merged_segments.last_mut() {
+                Some(last_segment)
+                    if last_segment.offset == segment.offset
+                        && last_segment.source.upper + 1 == segment.source.lower =>
+                {
+                    last_segment.source.upper = segment.source.upper;
                 }
+                _ => merged_segments.push(segment),
             }
-            counter += 1;
+        }
+        PiecewiseMap {
+            segments: merged_segments,
         }
     }
-    let mut unmapped_range_map = HashMap::new();
-    for remaining_range in potential_holes {
-        let delta_lower = remaining_range.lower - current_dest_range.lower;
-        let delta_upper = remaining_range.upper - current_dest_range.upper;
-        unmapped_range_map.insert(
-            Range {
-                lower: current_src_range.lower + delta_lower,
-                upper: current_src_range.upper + delta_upper,
+
+    fn full_segments(&self) -> Vec<MapSegment> {
+        //! The stored segments, plus segments with an offset of 0 filling every gap,
+        //! so that together they cover the whole domain.
+        let mut full_segments = Vec::with_capacity(self.segments.len() * 2 + 1);
+        let mut next_uncovered = 0;
+        for segment in self.segments.iter() {
+            if segment.source.lower > next_uncovered {
+                full_segments.push(MapSegment {
+                    source: Range {
+                        lower: next_uncovered,
+                        upper: segment.source.lower - 1,
+                    },
+                    offset: 0,
+                });
             }
-            .into_ordered_pair(),
-            remaining_range.clone().into_ordered_pair(),
-        );
+            full_segments.push(segment.clone());
+            next_uncovered = segment.source.upper + 1;
+        }
+        if next_uncovered <= DOMAIN_UPPER {
+            full_segments.push(MapSegment {
+                source: Range {
+                    lower: next_uncovered,
+                    upper: DOMAIN_UPPER,
+                },
+                offset: 0,
+            });
+        }
+        full_segments
+    }
+
+    fn segment_for(&self, value: isize) -> Option<&MapSegment> {
+        //! The stored segment whose source contains the value, if any.
+        let index = self
+            .segments
+            .partition_point(|segment| segment.source.upper < value);
+        self.segments
+            .get(index)
+            .filter(|segment| segment.source.lower <= value)
+    }
+
+    fn apply(&self, value: usize) -> usize {
+        //! Map a single value.
+        let value = value as isize;
+        match self.segment_for(value) {
+            Some(segment) => (value + segment.offset) as usize,
+            None => value as usize,
+        }
+    }
+
+    fn apply_to_ranges(&self, ranges: &[Range]) -> Vec<Range> {
+        //! Map every value in the ranges, returning the sorted and merged set of ranges
+        //! the values end up in. Parts of the ranges that are not covered by any segment map to themselves.
+        let full_segments = self.full_segments();
+        let mut mapped_ranges = Vec::new();
+        for range in ranges {
+            for segment in full_segments.iter() {
+                if let Some(covered) = range.intersection(&segment.source) {
+                    mapped_ranges.push(covered.shifted(segment.offset));
+                }
+            }
+        }
+        merge_ranges(mapped_ranges)
+    }
+
+    fn minimum_over_ranges(&self, ranges: &[Range]) -> Option<(usize, usize)> {
+        //! The `(value, mapped value)` pair with the smallest mapped value out of all
+        //! the values in the ranges. Within a segment the smallest mapped value always
+        //! comes from the smallest value, so only the start of each piece needs checking.
+        let full_segments = self.full_segments();
+        ranges
+            .iter()
+            .flat_map(|range| {
+                full_segments.iter().filter_map(|segment| {
+                    let covered = range.intersection(&segment.source)?;
+                    Some((
+                        covered.lower as usize,
+                        (covered.lower + segment.offset) as usize,
+                    ))
+                })
+            })
+            .min_by_key(|&(_, mapped_value)| mapped_value)
     }
-    unmapped_range_map
+
+    fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        //! Compose two maps, so that the output of `self` is the input to `next`.
+        //! Each segment of `self` is split up wherever its image crosses a boundary
+        //! between the segments of `next`.
+        let next_full_segments = next.full_segments();
+        let mut composed_segments = Vec::new();
+        for segment in self.full_segments() {
+            let image = segment.image();
+            for next_segment in next_full_segments.iter() {
+                if let Some(shared) = image.intersection(&next_segment.source) {
+                    composed_segments.push(MapSegment {
+                        source: shared.shifted(-segment.offset),
+                        offset: segment.offset + next_segment.offset,
+                    });
+                }
+            }
+        }
+        PiecewiseMap::from_segments(composed_segments)
+    }
+
+    fn invert(&self) -> Option<PiecewiseMap> {
+        //! The inverse map, which only exists if no two values map to the same value.
+        let mut inverse_segments = self
+            .full_segments()
+            .into_iter()
+            .map(|segment| MapSegment {
+                source: segment.image(),
+                offset: -segment.offset,
+            })
+            .collect::<Vec<_>>();
+        inverse_segments.sort_by_key(|segment| segment.source.lower);
+        for pair in inverse_segments.windows(2) {
+            if pair[0].source.overlaps(&pair[1].source) {
+                return None;
+            }
+        }
+        Some(PiecewiseMap::from_segments(inverse_segments))
+    }
+}
+
+fn subtract_range(range: &Range, to_remove: &Range) -> Vec<Range> {
+    //! The parts of `range` that are not in `to_remove`, of which there are at most two.
+    if !range.overlaps(to_remove) {
+        return vec![range.clone()];
+    }
+    [
+        Range {
+            lower: range.lower,
+            upper: to_remove.lower - 1,
+        },
+        Range {
+            lower: to_remove.upper + 1,
+            upper: range.upper,
+        },
+    ]
+    .into_iter()
+    .filter(|remaining| remaining.is_valid_range())
+    .collect::<Vec<_>>()
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
     #[test]
-    fn test_merge_maps1() {
-        let current_map_src = Range::new(5, 12);
-        let current_map_dest = Range::new(16, 23);
-        let next_map_src = Range::new(18, 21);
-        let next_map_dest = Range::new(101, 104);
-        let merged_map = apply_range_mapping(
-            current_map_src,
-            current_map_dest,
-            next_map_src,
-            next_map_dest,
+    fn test_example_almanac() {
+        let almanac = Almanac::new(EXAMPLE_INPUT).unwrap();
+        let seed_to_location = almanac.chain("seed", "location").unwrap();
+        let locations = almanac
+            .seeds
+            .iter()
+            .map(|seed| seed_to_location.apply(*seed))
+            .collect::<Vec<_>>();
+        assert_eq!(locations, vec![82, 43, 86, 35]);
+        assert_eq!(
+            find_location_using_ranges(almanac.seeds.clone(), &seed_to_location),
+            46
         );
-        assert_eq!(merged_map.len(), 1);
-        assert_eq!(merged_map.get(&(7, 10)).unwrap(), &(101, 104));
+        //Chains can start and end at any category.
+        let soil_to_water = almanac.chain("soil", "water").unwrap();
+        assert_eq!(soil_to_water.apply(81), 81);
+        assert_eq!(soil_to_water.apply(14), 49);
     }
 
     #[test]
-    fn test_merge_maps2() {
-        let current_map_src = Range::new(5, 8);
-        let current_map_dest = Range::new(9, 12);
-        let next_map_src = Range::new(8, 10);
-        let next_map_dest = Range::new(42, 44);
-        let merged_map = apply_range_mapping(
-            current_map_src,
-            current_map_dest,
-            next_map_src,
-            next_map_dest,
-        );
-        assert_eq!(merged_map.len(), 1);
-        assert_eq!(merged_map.get(&(5, 6)).unwrap(), &(43, 44));
+    fn test_trace() {
+        let almanac = Almanac::new(EXAMPLE_INPUT).unwrap();
+        let steps = almanac.trace(82, "seed", "location").unwrap();
+        let values = steps.iter().map(|step| step.next_value).collect::<Vec<_>>();
+        assert_eq!(values, vec![84, 84, 84, 77, 45, 46, 46]);
+        assert_eq!(steps[0].fired_line, Some((52, 50, 48)));
+        assert_eq!(steps[1].fired_line, None);
+        assert_eq!(steps[4].category, "light");
+        assert_eq!(steps[4].fired_line, Some((45, 77, 23)));
+        assert_eq!(steps[6].next_category, "location");
     }
+
     #[test]
-    fn test_merge_maps3() {
-        let current_map_src = Range::new(7, 9);
-        let current_map_dest = Range::new(8, 10);
-        let next_map_src = Range::new(11, 13);
-        let next_map_dest = Range::new(12, 14);
-        let merged_map = apply_range_mapping(
-            current_map_src,
-            current_map_dest,
-            next_map_src,
-            next_map_dest,
+    fn test_minimum_over_ranges() {
+        let almanac = Almanac::new(EXAMPLE_INPUT).unwrap();
+        let seed_to_location = almanac.chain("seed", "location").unwrap();
+        let seed_ranges = [Range::new(79, 92), Range::new(55, 67)];
+        assert_eq!(
+            seed_to_location.minimum_over_ranges(&seed_ranges),
+            Some((82, 46))
+        );
+        assert_eq!(
+            seed_to_location.minimum_over_ranges(&[Range::new(13, 13)]),
+            Some((13, 35))
         );
-        assert_eq!(merged_map.len(), 0);
+        assert_eq!(seed_to_location.minimum_over_ranges(&[]), None);
     }
 
     #[test]
-    fn test_hole_filling1() {
-        let holes = fill_unmapped_ranges(
-            Range::new(8, 20),
-            Range::new(8, 20),
-            vec![Range::new(9, 13), Range::new(15, 17), Range::new(18, 20)],
+    fn test_extra_categories() {
+        let almanac = Almanac::new(
+            "seeds: 1 2
+
+seed-to-moon map:
+10 0 5
+
+moon-to-location map:
+100 12 1
+",
+        )
+        .unwrap();
+        let seed_to_location = almanac.chain("seed", "location").unwrap();
+        assert_eq!(seed_to_location.apply(1), 11);
+        assert_eq!(seed_to_location.apply(2), 100);
+        //12 is not moved by the seed map, but is by the moon map.
+        assert_eq!(seed_to_location.apply(12), 100);
+        assert_eq!(seed_to_location.apply(13), 13);
+    }
+
+    #[test]
+    fn test_crlf_and_invalid_almanacs() {
+        let crlf_almanac = Almanac::new(&EXAMPLE_INPUT.replace('\n', "\r\n")).unwrap();
+        assert_eq!(crlf_almanac.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(
+            crlf_almanac.chain("seed", "location").unwrap().apply(82),
+            46
         );
-        assert_eq!(holes.len(), 2);
-        assert!(holes.contains_key(&(8, 8)));
-        assert!(holes.contains_key(&(14, 14)));
+
+        assert!(Almanac::new("").is_err());
+        assert!(Almanac::new("seeds: 1 x").is_err());
+        assert!(Almanac::new("plants: 1 2").is_err());
+        assert!(Almanac::new("seeds: 1\n\nseed map:\n1 2 3").is_err());
+        assert!(Almanac::new("seeds: 1\n\nseed-to-soil map:\n1 2").is_err());
     }
 
     #[test]
-    fn test_hole_filling2() {
-        let holes = fill_unmapped_ranges(
-            Range::new(8, 20),
-            Range::new(8, 20),
-            vec![Range::new(7, 21)],
+    fn test_cyclic_almanac() {
+        let almanac = Almanac::new(
+            "seeds: 1
+
+seed-to-soil map:
+10 0 5
+
+soil-to-water map:
+20 10 5
+
+water-to-soil map:
+10 20 5
+",
+        )
+        .unwrap();
+        assert_eq!(
+            almanac.chain("seed", "location").unwrap_err(),
+            "The maps loop back to soil without reaching location"
         );
-        assert_eq!(holes.len(), 0);
+        assert!(almanac.trace(1, "seed", "location").is_err());
+        assert!(almanac.chain("water", "seed").is_err());
+        assert_eq!(almanac.chain("seed", "water").unwrap().apply(1), 21);
     }
+
     #[test]
-    fn test_hole_filling3() {
-        let holes = fill_unmapped_ranges(
-            Range::new(8, 20),
-            Range::new(8, 20),
-            vec![Range::new(9, 21)],
+    fn test_compose_maps1() {
+        let current_map = PiecewiseMap::from_mapping_lines(&[(16, 5, 8)]);
+        let next_map = PiecewiseMap::from_mapping_lines(&[(101, 18, 4)]);
+        let composed = current_map.then(&next_map);
+        assert_eq!(composed.apply(7), 101);
+        assert_eq!(composed.apply(10), 104);
+        assert_eq!(composed.apply(6), 17);
+        assert_eq!(composed.apply(11), 22);
+        assert_eq!(composed.apply(18), 101);
+        assert_eq!(
+            composed.apply_to_ranges(&[Range::new(5, 12)]),
+            vec![Range::new(16, 17), Range::new(22, 23), Range::new(101, 104)]
         );
-        assert_eq!(holes.len(), 1);
-        assert!(holes.contains_key(&(8, 8)));
     }
+
     #[test]
-    fn test_hole_filling4() {
-        let holes = fill_unmapped_ranges(
-            Range::new(8, 20),
-            Range::new(8, 20),
-            vec![Range::new(7, 15)],
+    fn test_compose_maps2() {
+        let current_map = PiecewiseMap::from_mapping_lines(&[(9, 5, 4)]);
+        let next_map = PiecewiseMap::from_mapping_lines(&[(42, 8, 3)]);
+        let composed = current_map.then(&next_map);
+        assert_eq!(
+            composed.apply_to_ranges(&[Range::new(5, 8)]),
+            vec![Range::new(11, 12), Range::new(43, 44)]
         );
-        assert_eq!(holes.len(), 1);
-        assert!(holes.contains_key(&(16, 20)));
     }
+
+    #[test]
+    fn test_compose_is_sequential_application() {
+        let almanac = Almanac::new(EXAMPLE_INPUT).unwrap();
+        let seed_to_location = almanac.chain("seed", "location").unwrap();
+        for seed in 0..120 {
+            let mut category = "seed";
+            let mut value = seed;
+            while let Some(category_map) = almanac.maps.get(category) {
+                value = category_map.map.apply(value);
+                category = &category_map.destination;
+            }
+            assert_eq!(seed_to_location.apply(seed), value);
+        }
+    }
+
     #[test]
-    fn test_hole_filling5() {
-        let holes = fill_unmapped_ranges(
-            Range::new(15, 27),
-            Range::new(15, 27),
-            vec![Range::new(8, 15)],
+    fn test_unmapped_ranges_map_to_themselves() {
+        let map = PiecewiseMap::from_mapping_lines(&[(109, 9, 5), (115, 15, 3), (118, 18, 3)]);
+        let mapped = map.apply_to_ranges(&[Range::new(8, 20)]);
+        assert!(mapped.contains(&Range::new(8, 8)));
+        assert!(mapped.contains(&Range::new(14, 14)));
+        let map = PiecewiseMap::from_mapping_lines(&[(107, 7, 15)]);
+        assert_eq!(
+            map.apply_to_ranges(&[Range::new(8, 20)]),
+            vec![Range::new(108, 120)]
         );
-        assert_eq!(holes.len(), 1);
-        assert!(holes.contains_key(&(16, 27)));
+        let map = PiecewiseMap::from_mapping_lines(&[(107, 7, 9)]);
+        assert_eq!(
+            map.apply_to_ranges(&[Range::new(8, 20)]),
+            vec![Range::new(16, 20), Range::new(108, 115)]
+        );
+    }
+
+    #[test]
+    fn test_earlier_mapping_lines_win() {
+        let map = PiecewiseMap::from_mapping_lines(&[(100, 10, 5), (200, 8, 10)]);
+        assert_eq!(map.apply(9), 201);
+        assert_eq!(map.apply(12), 102);
+        assert_eq!(map.apply(16), 208);
+    }
+
+    #[test]
+    fn test_invert() {
+        let almanac = Almanac::new(EXAMPLE_INPUT).unwrap();
+        let seed_to_location = almanac.chain("seed", "location").unwrap();
+        let location_to_seed = seed_to_location.invert().unwrap();
+        for seed in 0..200 {
+            assert_eq!(location_to_seed.apply(seed_to_location.apply(seed)), seed);
+        }
+        assert_eq!(location_to_seed.apply(46), 82);
+        //Both 0 and 10 map to 10, so there is no inverse.
+        let not_invertible = PiecewiseMap::from_mapping_lines(&[(10, 0, 1)]);
+        assert_eq!(not_invertible.invert(), None);
     }
 
     #[test]
@@ -1,9 +1,10 @@
+use crate::math::isqrt;
 use crate::{read_input_file, SolveAdvent};
 
 pub struct Day6;
 
 impl SolveAdvent for Day6 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let files_as_str = read_input_file(path_to_file);
         let processed_race_numbers = process_input_file(files_as_str);
         let mut multiplicative_ways_to_win = 1;
@@ -12,26 +13,39 @@ impl SolveAdvent for Day6 {
             multiplicative_ways_to_win *= ways_to_win;
         }
         println!("Multiplicative Ways to Win: {}", multiplicative_ways_to_win);
+        multiplicative_ways_to_win.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let files_as_str = read_input_file(path_to_file);
         let (required_time, required_distance) = process_input_file_part2(files_as_str);
         let ways_to_win = ways_to_win_race(required_distance, required_time);
         println!("Ways to win single race: {}", ways_to_win);
+        ways_to_win.to_string()
     }
 }
 
 fn ways_to_win_race(required_distance: usize, required_time: usize) -> usize {
-    let mut ways_to_win = 0;
-    for time_holding_button in 1..required_time {
-        let time_to_move = required_time - time_holding_button;
-        let distance_traveled = time_to_move * time_holding_button;
-        if distance_traveled > required_distance {
-            ways_to_win += 1;
-        }
+    //! Holding the button for `h` travels `h * (time - h)`, so the winning hold times
+    //! are those strictly between the roots of `h^2 - time*h + distance = 0`, which are
+    //! symmetric around `time / 2`.
+    let (time, distance) = (required_time as u128, required_distance as u128);
+    let beats_record =
+        |time_holding_button: u128| time_holding_button * (time - time_holding_button) > distance;
+    if !beats_record(time / 2) {
+        return 0;
+    }
+    //The integer square root of the discriminant puts the lower root within one of the
+    //exact value, so nudge it until it is the first hold time that wins.
+    let discriminant = (time * time).saturating_sub(4 * distance);
+    let mut lowest_winning_hold = (time - isqrt(discriminant)) / 2;
+    while !beats_record(lowest_winning_hold) {
+        lowest_winning_hold += 1;
+    }
+    while lowest_winning_hold > 0 && beats_record(lowest_winning_hold - 1) {
+        lowest_winning_hold -= 1;
     }
-    ways_to_win
+    (time - 2 * lowest_winning_hold + 1) as usize
 }
 
 fn process_input_file(file_as_str: String) -> Vec<(usize, usize)> {
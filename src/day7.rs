@@ -1,91 +1,289 @@
 use crate::{read_input_file, SolveAdvent};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct Day7;
 
-const AVAILABLE_CARDS: [char; 12] = ['A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2'];
-
 impl SolveAdvent for Day7 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
-        let mut processed_hands = process_into_poker_hands(file_as_str, false);
-        //Because Ord trait is implemented for PokerHand, we can just sort the vec.
-        processed_hands.sort();
-
-        let mut total_winnings = 0;
-        for (rank, poker_hand) in processed_hands.iter().enumerate() {
-            total_winnings += (rank + 1) * poker_hand.wager;
-        }
+        let total_winnings = compute_total_winnings(&file_as_str, &RuleSet::part1());
         println!("Total Winnings: {}", total_winnings);
+        total_winnings.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
+        //! The J cards are wild, and count as the weakest card when breaking ties.
         let file_as_str = read_input_file(path_to_file);
-        let mut poker_hands = process_into_poker_hands(file_as_str, true);
-        for hand in poker_hands.iter_mut() {
-            //For each hand, compute the best possible HandType using the wildcards.
-            //Reset the hand_type field to this new optimized hand_type.
-            let hand_type_with_wildcard = hand.compute_wildcard_handtype();
-            hand.hand_type = hand_type_with_wildcard;
-        }
+        let total_winnings = compute_total_winnings(&file_as_str, &RuleSet::part2());
+        println!("Total Winnings: {}", total_winnings);
+        total_winnings.to_string()
+    }
+}
 
-        // Because Ord trait is implemented for PokerHand, we can just sort the vec.
-        poker_hands.sort();
+pub fn solve_with_rules(path_to_file: &str, rules_path: &str) -> Result<String, String> {
+    //! Solve the puzzle using the custom rule set in the `rules_path` file.
+    let rules = RuleSet::from_config(&read_input_file(rules_path))?;
+    let file_as_str = read_input_file(path_to_file);
+    rules.check_cards(&file_as_str)?;
+    let total_winnings = compute_total_winnings(&file_as_str, &rules);
+    println!("Total Winnings using {}: {}", rules_path, total_winnings);
+    Ok(total_winnings.to_string())
+}
 
-        let mut total_winnings = 0;
-        for (rank, poker_hand) in poker_hands.iter().enumerate() {
-            total_winnings += (rank + 1) * poker_hand.wager;
-        }
-        println!("Total Winnings: {}", total_winnings);
+fn compute_total_winnings(file_as_str: &str, rules: &RuleSet) -> usize {
+    //! Rank every hand using the rules, and sum each wager multiplied by its rank.
+    let mut poker_hands = process_into_poker_hands(file_as_str, rules);
+    poker_hands.sort_by(|hand1, hand2| rules.compare(hand1, hand2));
+
+    let mut total_winnings = 0;
+    for (rank, poker_hand) in poker_hands.iter().enumerate() {
+        total_winnings += (rank + 1) * poker_hand.wager;
     }
+    total_winnings
 }
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 struct PokerHand {
-    hand_type: HandType,
+    ///The position of the hand's type in the `RuleSet` hand type table,
+    /// so a lower hand_type is a better hand.
+    hand_type: usize,
     hand: String,
     wager: usize,
-    ///use_wildcard field is required to communicate to the Ord and PartialOrd
-    /// impl whether or not to count the J as a 1 or a 11 when comparing card-by-card
-    use_wildcard: bool,
 }
 
-///Enum representing all possible Poker hand types in this game.
-#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Copy)]
-enum HandType {
-    FiveOfAKind,
-    FourOfAKind,
-    FullHouse,
-    ThreeOfAKind,
-    TwoPair,
-    Pair,
-    HighCard,
+impl PokerHand {
+    fn new(hand: &str, wager: usize, rules: &RuleSet) -> PokerHand {
+        //! Given the hand, figure out which hand type the hand represents under the rules,
+        //! and returns a `PokerHand`. If there are wildcards, the best possible hand type is chosen.
+        PokerHand {
+            hand_type: rules.hand_type_index(hand),
+            hand: hand.to_owned(),
+            wager,
+        }
+    }
+}
+
+///How to compare two hands that have the same hand type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TieBreak {
+    ///Compare the cards one-by-one, in the order they were dealt, as the puzzle describes.
+    FirstCard,
+    ///Compare the cards one-by-one from the strongest to the weakest, as in regular poker.
+    HighestCard,
 }
 
-impl PokerHand {
-    fn compute_wildcard_handtype(&self) -> HandType {
-        //! Recursively explore different PokerHand options by replacing
-        //! the J's (the wildcard) with a different card.
+///A named hand type, such as a full house.
+#[derive(Debug, Clone, PartialEq)]
+struct HandTypeRule {
+    name: String,
+    ///The smallest group sizes a hand needs, largest first. For example a
+    /// full house needs `[3, 2]`: a group of three, and another group of two.
+    min_group_sizes: Vec<usize>,
+}
+
+///The rules of a game of Camel Cards, which decide how every pair of hands compares.
+#[derive(Debug, Clone, PartialEq)]
+struct RuleSet {
+    ///Every card, from the weakest to the strongest.
+    rank_order: Vec<char>,
+    ///The cards that are wild. Wild cards join whichever groups of matching cards make
+    /// the best hand type, but keep their own rank when breaking ties.
+    wild_ranks: Vec<char>,
+    tie_break: TieBreak,
+    ///The hand types from the strongest to the weakest. A hand has the first type
+    /// it qualifies for, and hands that qualify for none are the weakest of all.
+    hand_types: Vec<HandTypeRule>,
+}
+
+impl RuleSet {
+    fn part1() -> RuleSet {
+        RuleSet {
+            rank_order: "23456789TJQKA".chars().collect::<Vec<_>>(),
+            wild_ranks: Vec::new(),
+            tie_break: TieBreak::FirstCard,
+            hand_types: default_hand_types(),
+        }
+    }
 
-        //If the hand passed does not contain a J, then no more recursion is required.
-        if !self.hand.contains(&'J'.to_string()) {
-            return self.hand_type;
+    fn part2() -> RuleSet {
+        //! As described in the problem directions, the J is wild, and is the weakest card.
+        RuleSet {
+            rank_order: "J23456789TQKA".chars().collect::<Vec<_>>(),
+            wild_ranks: vec!['J'],
+            tie_break: TieBreak::FirstCard,
+            hand_types: default_hand_types(),
         }
-        let mut optional_hands = Vec::new();
-        for card in AVAILABLE_CARDS {
-            let new_hand_with_wildcard = self.hand.replacen('J', &card.to_string(), 1);
-            let new_poker_hand = PokerHand::new(&new_hand_with_wildcard, self.wager, true);
-            //Recursively compute the next wildcard replacement optimization.
-            let optional_hand = new_poker_hand.compute_wildcard_handtype();
-            optional_hands.push(optional_hand);
+    }
+
+    fn from_config(config: &str) -> Result<RuleSet, String> {
+        //! Build a rule set from a config file, with one `key = value` setting per line.
+        //! Blank lines and lines starting with `#` are ignored. Any setting that is left
+        //! out keeps its part1 value. For example:
+        //! ```text
+        //! ranks = J23456789TQKA
+        //! wild = J
+        //! tie_break = highest_card
+        //! type FiveOfAKind = 5
+        //! type FullHouse = 3 2
+        //! type HighCard =
+        //! ```
+        //! Giving any `type` lines replaces the whole hand type table, strongest first.
+        let mut rules = RuleSet::part1();
+        let mut hand_types = Vec::new();
+        for line in config.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Expected `key = value`, got `{}`", line))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "ranks" => rules.rank_order = value.chars().collect::<Vec<_>>(),
+                "wild" => rules.wild_ranks = value.chars().collect::<Vec<_>>(),
+                "tie_break" => {
+                    rules.tie_break = match value {
+                        "first_card" => TieBreak::FirstCard,
+                        "highest_card" => TieBreak::HighestCard,
+                        other => return Err(format!("Unknown tie_break `{}`", other)),
+                    }
+                }
+                _ => {
+                    let name = key
+                        .strip_prefix("type ")
+                        .ok_or_else(|| format!("Unknown setting `{}`", key))?;
+                    let min_group_sizes = value
+                        .split_whitespace()
+                        .map(|size| {
+                            size.parse::<usize>()
+                                .map_err(|_| format!("Invalid group size `{}` for {}", size, name))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    hand_types.push(HandTypeRule {
+                        name: name.trim().to_owned(),
+                        min_group_sizes,
+                    });
+                }
+            }
+        }
+        if !hand_types.is_empty() {
+            rules.hand_types = hand_types;
+        }
+        if let Some(card) = rules
+            .wild_ranks
+            .iter()
+            .find(|card| !rules.rank_order.contains(card))
+        {
+            return Err(format!("Wild card {} is missing from the ranks", card));
+        }
+        if let Some((position, card)) = rules
+            .rank_order
+            .iter()
+            .enumerate()
+            .find(|(position, card)| rules.rank_order[..*position].contains(card))
+        {
+            return Err(format!(
+                "Card {} is listed twice in the ranks, the second time at position {}",
+                card,
+                position + 1
+            ));
+        }
+        Ok(rules)
+    }
+
+    fn check_cards(&self, file_as_str: &str) -> Result<(), String> {
+        //! Make sure every card in the hands of the input is in the rank order,
+        //! as a custom rank order may leave some out.
+        for line in file_as_str.lines() {
+            let hand = line.split(' ').next().unwrap_or_default().trim();
+            if let Some(card) = hand.chars().find(|card| !self.rank_order.contains(card)) {
+                return Err(format!(
+                    "Card {} in the hand {} is missing from the ranks",
+                    card, hand
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn rank_of(&self, card: char) -> usize {
+        //! The strength of the card, where a higher number is a stronger card.
+        self.rank_order
+            .iter()
+            .position(|&ranked_card| ranked_card == card)
+            .unwrap_or_else(|| panic!("Card {} is not in the rank order", card))
+    }
+
+    fn hand_type_index(&self, hand: &str) -> usize {
+        //! The position of the first hand type in the table that the hand qualifies for,
+        //! using the wildcards in whichever way gives the best position.
+        group_size_choices(hand, &self.wild_ranks)
+            .iter()
+            .map(|group_sizes| self.group_sizes_type_index(group_sizes))
+            .min()
+            .unwrap_or(self.hand_types.len())
+    }
+
+    fn group_sizes_type_index(&self, group_sizes: &[usize]) -> usize {
+        //! The position of the first hand type in the table that the group sizes qualify for.
+        self.hand_types
+            .iter()
+            .position(|hand_type| {
+                hand_type.min_group_sizes.len() <= group_sizes.len()
+                    && hand_type
+                        .min_group_sizes
+                        .iter()
+                        .zip(group_sizes.iter())
+                        .all(|(needed, group_size)| group_size >= needed)
+            })
+            .unwrap_or(self.hand_types.len())
+    }
+
+    fn compare(&self, hand1: &PokerHand, hand2: &PokerHand) -> Ordering {
+        //! Order the hands from the weakest to the strongest.
+        //! Because hand types are listed strongest first, a higher hand_type is worse.
+        hand2.hand_type.cmp(&hand1.hand_type).then_with(|| {
+            //If the two hands are of the same type, then compare the cards one-by-one to determine which hand is better.
+            self.tie_break_ranks(&hand1.hand)
+                .cmp(&self.tie_break_ranks(&hand2.hand))
+        })
+    }
+
+    fn tie_break_ranks(&self, hand: &str) -> Vec<usize> {
+        //! The card ranks of the hand, in the order the tie break compares them.
+        let mut ranks = hand
+            .chars()
+            .map(|card| self.rank_of(card))
+            .collect::<Vec<_>>();
+        if self.tie_break == TieBreak::HighestCard {
+            ranks.sort_unstable_by(|a, b| b.cmp(a));
         }
-        //Return the minimum handtype possible, which is the best hand possible.
-        optional_hands.into_iter().min().unwrap()
+        ranks
     }
 }
 
-fn process_into_poker_hands(file_as_str: String, use_wildcard: bool) -> Vec<PokerHand> {
+fn default_hand_types() -> Vec<HandTypeRule> {
+    //! The hand types of the puzzle. This works for any hand size, with a group
+    //! of five or more counting as five of a kind.
+    [
+        ("FiveOfAKind", vec![5]),
+        ("FourOfAKind", vec![4]),
+        ("FullHouse", vec![3, 2]),
+        ("ThreeOfAKind", vec![3]),
+        ("TwoPair", vec![2, 2]),
+        ("Pair", vec![2]),
+        ("HighCard", vec![]),
+    ]
+    .into_iter()
+    .map(|(name, min_group_sizes)| HandTypeRule {
+        name: name.to_owned(),
+        min_group_sizes,
+    })
+    .collect::<Vec<_>>()
+}
+
+fn process_into_poker_hands(file_as_str: &str, rules: &RuleSet) -> Vec<PokerHand> {
     //! Iterate over the input file, building each hand into a `PokerHand` type.
     file_as_str
         .lines()
@@ -98,116 +296,319 @@ fn process_into_poker_hands(file_as_str: String, use_wildcard: bool) -> Vec<Poke
                 .trim()
                 .parse::<usize>()
                 .unwrap();
-            PokerHand::new(hand, wager, use_wildcard)
+            PokerHand::new(hand, wager, rules)
         })
         .collect::<Vec<_>>()
 }
 
-fn map_card_to_num(card: char, use_wildcards: bool) -> usize {
-    //! If the card is a face card, return a corresponding
-    //! number to make numerical comparison easier.
-    //! As described in the problem directions, if wildcards are counted,
-    //! then wildcards count as 1, not the usual 11.
-    if card == 'A' {
-        return 14;
-    } else if card == 'K' {
-        return 13;
-    } else if card == 'Q' {
-        return 12;
-    } else if card == 'J' && !use_wildcards {
-        return 11;
-    } else if card == 'J' && use_wildcards {
-        return 1;
-    } else if card == 'T' {
-        return 10;
-    }
-    //If the card is a digit, convert to usize and return.
-    const RADIX: u32 = 10;
-    card.to_digit(RADIX).unwrap() as usize
+fn group_sizes(hand: &str, wild_cards: &[char]) -> Vec<usize> {
+    //! Returns the size of each group of matching cards in the hand, largest first.
+    //! Every wildcard joins the largest group, which gives the largest group sizes. That is the
+    //! best hand for the puzzle's table, where bigger groups always rank higher, but a custom
+    //! table may prefer another choice from `group_size_choices`. Because only the counts
+    //! matter, this works for any hand size and any set of cards, with any number of wild ranks.
+    let mut card_counter: HashMap<char, usize> = HashMap::with_capacity(hand.len());
+    let mut wildcard_count = 0;
+    for card in hand.chars() {
+        if wild_cards.contains(&card) {
+            wildcard_count += 1;
+        } else {
+            *card_counter.entry(card).or_default() += 1;
+        }
+    }
+    let mut group_sizes = card_counter.into_values().collect::<Vec<_>>();
+    group_sizes.sort_unstable_by(|a, b| b.cmp(a));
+    match group_sizes.first_mut() {
+        Some(largest_group) => *largest_group += wildcard_count,
+        //The hand is entirely wildcards.
+        None if wildcard_count > 0 => group_sizes.push(wildcard_count),
+        None => {}
+    }
+    group_sizes
 }
 
-impl PartialOrd for PokerHand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+fn group_size_choices(hand: &str, wild_cards: &[char]) -> HashSet<Vec<usize>> {
+    //! Every set of group sizes, largest first, that the hand can make. Each wildcard either
+    //! joins one of the groups, or starts a new group, which is the same as substituting
+    //! every possible card, but only needs the counts.
+    let wildcard_count = hand
+        .chars()
+        .filter(|card| wild_cards.contains(card))
+        .count();
+    let other_cards = hand
+        .chars()
+        .filter(|card| !wild_cards.contains(card))
+        .collect::<String>();
+    let mut choices = HashSet::from([group_sizes(&other_cards, &[])]);
+    for _ in 0..wildcard_count {
+        let mut next_choices = HashSet::new();
+        for group_sizes in &choices {
+            for joined_group in 0..=group_sizes.len() {
+                let mut next_group_sizes = group_sizes.clone();
+                match next_group_sizes.get_mut(joined_group) {
+                    Some(group_size) => *group_size += 1,
+                    None => next_group_sizes.push(1),
+                }
+                next_group_sizes.sort_unstable_by(|a, b| b.cmp(a));
+                next_choices.insert(next_group_sizes);
+            }
+        }
+        choices = next_choices;
     }
+    choices
 }
 
-impl Ord for PokerHand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        //! Partial ORD implementation is already good enough
-        if self.hand_type == other.hand_type && self.hand == other.hand {
-            return Ordering::Equal;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    const AVAILABLE_CARDS: [char; 12] =
+        ['A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2'];
+
+    fn recursive_wildcard_handtype(hand: &str) -> usize {
+        //! The original approach: recursively replace each J with every other card,
+        //! and keep the best hand type found.
+        if !hand.contains('J') {
+            return RuleSet::part1().hand_type_index(hand);
         }
-        //Because ranks start at 0 and count up, a higher hand_type in our designation is bad.
-        match self.hand_type.cmp(&other.hand_type) {
-            Ordering::Greater => {
-                return Ordering::Less;
-            }
-            Ordering::Less => {
-                return Ordering::Greater;
-            }
-            Ordering::Equal => {}
-        }
-        //If the two hands are of the same rank, then compare each char one-by-one to determine which hand is better.
-        for (char1, char2) in self.hand.chars().zip(other.hand.chars()) {
-            let char1_as_num = map_card_to_num(char1, self.use_wildcard);
-            let char2_as_num = map_card_to_num(char2, other.use_wildcard);
-            match char1_as_num.cmp(&char2_as_num) {
-                Ordering::Greater => {
-                    return Ordering::Greater;
+        AVAILABLE_CARDS
+            .iter()
+            .map(|card| recursive_wildcard_handtype(&hand.replacen('J', &card.to_string(), 1)))
+            .min()
+            .unwrap()
+    }
+
+    fn recursive_group_sizes(hand: &str, wild_cards: &[char], deck: &[char]) -> Vec<usize> {
+        //! The same recursion, generalized to any deck and any wild ranks.
+        //! Group sizes sorted largest first compare in the same order as the hands they make.
+        match hand.chars().find(|card| wild_cards.contains(card)) {
+            None => group_sizes(hand, &[]),
+            Some(wild_card) => deck
+                .iter()
+                .filter(|card| !wild_cards.contains(card))
+                .map(|card| {
+                    let substituted = hand.replacen(wild_card, &card.to_string(), 1);
+                    recursive_group_sizes(&substituted, wild_cards, deck)
+                })
+                .max()
+                .unwrap_or_else(|| group_sizes(hand, wild_cards)),
+        }
+    }
+
+    impl RuleSet {
+        fn hand_type_name(&self, hand: &str) -> &str {
+            self.hand_types
+                .get(self.hand_type_index(hand))
+                .map_or("None", |hand_type| &hand_type.name)
+        }
+    }
+
+    fn all_hands(deck: &[char], hand_size: usize) -> Vec<String> {
+        //! Every ordered hand of the given size that can be dealt from the deck.
+        let mut hands = vec![String::new()];
+        for _ in 0..hand_size {
+            hands = hands
+                .iter()
+                .flat_map(|hand| deck.iter().map(move |card| format!("{}{}", hand, card)))
+                .collect::<Vec<_>>();
+        }
+        hands
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(
+            compute_total_winnings(EXAMPLE_INPUT, &RuleSet::part1()),
+            6440
+        );
+        assert_eq!(
+            compute_total_winnings(EXAMPLE_INPUT, &RuleSet::part2()),
+            5905
+        );
+    }
+
+    #[test]
+    fn test_counting_matches_recursion_for_all_hands() {
+        //The hand type only depends on which cards are in the hand, so the recursion only
+        //needs running once per sorted hand.
+        let deck = [
+            'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
+        ];
+        let rules = RuleSet::part2();
+        let mut recursive_hand_types = HashMap::new();
+        for hand in all_hands(&deck, 5) {
+            let mut sorted_hand = hand.chars().collect::<Vec<_>>();
+            sorted_hand.sort_unstable();
+            let sorted_hand = sorted_hand.into_iter().collect::<String>();
+            let expected = *recursive_hand_types
+                .entry(sorted_hand)
+                .or_insert_with_key(|sorted_hand| recursive_wildcard_handtype(sorted_hand));
+            assert_eq!(rules.hand_type_index(&hand), expected, "{}", hand);
+        }
+        assert_eq!(recursive_hand_types.len(), 6188);
+    }
+
+    #[test]
+    fn test_counting_matches_recursion_for_other_rules() {
+        let deck = ['A', 'B', 'C', 'W', 'X'];
+        for hand_size in 1..=6 {
+            for wild_cards in [&[][..], &['W'][..], &['W', 'X'][..]] {
+                for hand in all_hands(&deck, hand_size) {
+                    assert_eq!(
+                        group_sizes(&hand, wild_cards),
+                        recursive_group_sizes(&hand, wild_cards, &deck),
+                        "{} with {:?} wild",
+                        hand,
+                        wild_cards
+                    );
                 }
-                Ordering::Less => {
-                    return Ordering::Less;
+            }
+        }
+    }
+
+    #[test]
+    fn test_wildcards_with_reordered_tables() {
+        //A full house beats four of a kind, so the J should pair with the 2 rather than join the aces.
+        let rules = RuleSet::from_config(
+            "wild = J
+type FullHouse = 3 2
+type FourOfAKind = 4
+type Nothing =",
+        )
+        .unwrap();
+        assert_eq!(rules.hand_type_name("AAA2J"), "FullHouse");
+        assert_eq!(rules.hand_type_name("AAAAJ"), "FourOfAKind");
+        //Two pair beats three of a kind, so the two Js should split up.
+        let rules = RuleSet::from_config(
+            "wild = J
+type TwoPair = 2 2
+type Triple = 3
+type Nothing =",
+        )
+        .unwrap();
+        assert_eq!(rules.hand_type_name("A2JJ"), "TwoPair");
+        assert_eq!(rules.hand_type_name("AJ"), "Nothing");
+
+        //Check the choices against substituting every card, for every hand of up to 5 cards.
+        let deck = ['2', '3', '4', 'J'];
+        let mut substituted_rules = rules.clone();
+        substituted_rules.wild_ranks.clear();
+        for hand_size in 1..=5 {
+            for hand in all_hands(&deck, hand_size) {
+                let mut hands = vec![hand.clone()];
+                while hands.iter().any(|hand| hand.contains('J')) {
+                    hands = hands
+                        .iter()
+                        .flat_map(|hand| {
+                            ['2', '3', '4', 'A', 'K']
+                                .map(|card| hand.replacen('J', &card.to_string(), 1))
+                        })
+                        .collect::<Vec<_>>();
                 }
-                Ordering::Equal => {}
+                let best_substitution = hands
+                    .iter()
+                    .map(|hand| substituted_rules.hand_type_index(hand))
+                    .min()
+                    .unwrap();
+                assert_eq!(rules.hand_type_index(&hand), best_substitution, "{}", hand);
             }
         }
-        Ordering::Equal
     }
-}
 
-fn char_count(hand: &str) -> Vec<usize> {
-    //! Returns a Vec of the count of each char in the hand.
-    //! This can be used to deduce the type of hand present.
-    let mut char_counter: HashMap<char, usize> = HashMap::with_capacity(hand.len());
-    for char in hand.chars() {
-        *char_counter.entry(char).or_default() += 1;
+    #[test]
+    fn test_hand_types() {
+        let part1 = RuleSet::part1();
+        let part2 = RuleSet::part2();
+        assert_eq!(part2.hand_type_name("JJJJJ"), "FiveOfAKind");
+        assert_eq!(part1.hand_type_name("JJJJJ"), "FiveOfAKind");
+        assert_eq!(part2.hand_type_name("KTJJT"), "FourOfAKind");
+        assert_eq!(part1.hand_type_name("KTJJT"), "TwoPair");
+        assert_eq!(part2.hand_type_name("2345J"), "Pair");
+        assert_eq!(part1.hand_type_name("222333"), "FullHouse");
+        assert_eq!(part1.hand_type_name("222222"), "FiveOfAKind");
+        assert_eq!(part1.hand_type_name(""), "HighCard");
     }
-    char_counter.values().copied().collect::<Vec<_>>()
-}
 
-impl PokerHand {
-    fn new(hand: &str, wager: usize, use_wildcard: bool) -> PokerHand {
-        //! Given the hand, figure out which Poker hand the hand represents,
-        //! and returns a `PokerHand`
-        let char_count = char_count(hand);
-        let hand_type = if char_count.contains(&5) {
-            //Five of a kind
-            HandType::FiveOfAKind
-        } else if char_count.contains(&4) {
-            //4 of a kind
-            HandType::FourOfAKind
-        } else if char_count.contains(&3) && char_count.contains(&2) {
-            //Full house
-            HandType::FullHouse
-        } else if char_count.contains(&3) {
-            //Three of a kind
-            HandType::ThreeOfAKind
-        } else if char_count.iter().filter(|item| item == &&2_usize).count() == 2 {
-            //Two pair
-            HandType::TwoPair
-        } else if char_count.contains(&2) {
-            //Pair
-            HandType::Pair
-        } else {
-            //High Card
-            HandType::HighCard
+    #[test]
+    fn test_tie_breaks() {
+        let part1 = RuleSet::part1();
+        let part2 = RuleSet::part2();
+        let hand1 = PokerHand::new("JKKK2", 0, &part1);
+        let hand2 = PokerHand::new("QQQQ2", 0, &part1);
+        assert_eq!(part1.compare(&hand1, &hand2), Ordering::Less);
+        let hand1 = PokerHand::new("JKKK2", 0, &part2);
+        let hand2 = PokerHand::new("QQQQ2", 0, &part2);
+        //Both are four of a kind, but the J is now the weakest card.
+        assert_eq!(part2.compare(&hand1, &hand2), Ordering::Less);
+        let hand1 = PokerHand::new("QQQQ2", 0, &part2);
+        let hand2 = PokerHand::new("2QQQQ", 0, &part2);
+        assert_eq!(part2.compare(&hand1, &hand2), Ordering::Greater);
+
+        let highest_card = RuleSet {
+            tie_break: TieBreak::HighestCard,
+            ..RuleSet::part1()
         };
-        PokerHand {
-            hand_type,
-            hand: hand.to_owned(),
-            wager,
-            use_wildcard,
-        }
+        let hand1 = PokerHand::new("23456", 0, &highest_card);
+        let hand2 = PokerHand::new("65432", 0, &highest_card);
+        assert_eq!(highest_card.compare(&hand1, &hand2), Ordering::Equal);
+        let hand2 = PokerHand::new("23457", 0, &highest_card);
+        assert_eq!(highest_card.compare(&hand1, &hand2), Ordering::Less);
+    }
+
+    #[test]
+    fn test_config() {
+        let rules = RuleSet::from_config(
+            "# The puzzle's part2
+ranks = J23456789TQKA
+wild = J
+",
+        )
+        .unwrap();
+        assert_eq!(rules, RuleSet::part2());
+        assert_eq!(compute_total_winnings(EXAMPLE_INPUT, &rules), 5905);
+
+        //Two wild ranks, and a table where three of a kind beats two pair.
+        let rules = RuleSet::from_config(
+            "wild = 2J
+tie_break = highest_card
+type Triple = 3
+type TwoPair = 2 2
+type Nothing =
+",
+        )
+        .unwrap();
+        assert_eq!(rules.tie_break, TieBreak::HighestCard);
+        assert_eq!(rules.hand_type_name("2JQKA"), "Triple");
+        assert_eq!(rules.hand_type_name("33445"), "TwoPair");
+        assert_eq!(rules.hand_type_name("2JJJ3"), "Triple");
+        assert_eq!(rules.hand_type_name("34567"), "Nothing");
+
+        let no_high_card = RuleSet::from_config("type Pair = 2").unwrap();
+        assert_eq!(no_high_card.hand_type_index("34567"), 1);
+        assert!(RuleSet::from_config("wild = Z").is_err());
+        assert!(RuleSet::from_config("tie_break = random").is_err());
+        assert!(RuleSet::from_config("type Pair = two").is_err());
+        assert!(RuleSet::from_config("colour = red").is_err());
+    }
+
+    #[test]
+    fn test_config_rank_errors() {
+        assert_eq!(
+            RuleSet::from_config("ranks = 23456789TJQKAT"),
+            Err("Card T is listed twice in the ranks, the second time at position 14".to_owned())
+        );
+        //The ranks leave out the 2, which the example hands use.
+        let rules = RuleSet::from_config("ranks = 3456789TJQKA").unwrap();
+        assert_eq!(
+            rules.check_cards(EXAMPLE_INPUT),
+            Err("Card 2 in the hand 32T3K is missing from the ranks".to_owned())
+        );
+        assert_eq!(RuleSet::part1().check_cards(EXAMPLE_INPUT), Ok(()));
     }
 }
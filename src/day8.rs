@@ -1,10 +1,11 @@
+use crate::math::checked_lcm_of;
 use crate::{read_input_file, SolveAdvent};
 use std::collections::HashMap;
 
 pub struct Day8;
 
 impl SolveAdvent for Day8 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let (lr_pattern, lr_map) = process_input_file(file_as_str);
 
@@ -23,9 +24,10 @@ impl SolveAdvent for Day8 {
             }
         }
         println!("Reached ZZZ in {} steps", steps);
+        steps.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
         let file_as_str = read_input_file(path_to_file);
         let (lr_pattern, lr_map) = process_input_file(file_as_str);
         //Start at all nodes the end with A
@@ -38,7 +40,7 @@ impl SolveAdvent for Day8 {
         //Determine how many steps it takes starting at each A node to reach a node that ends in Z.
         for starting_node in starting_nodes {
             let mut current_position = starting_node.to_owned();
-            let mut steps = 0;
+            let mut steps: u64 = 0;
             for step in lr_pattern.chars().cycle() {
                 if current_position.ends_with('Z') {
                     break;
@@ -54,13 +56,18 @@ impl SolveAdvent for Day8 {
             steps_to_reach_ending_in_z.push(steps);
         }
         println!(
-            "Steps for each node to reach ending in Z: {:?}. The LCM of these numbers is the answer.",
+            "Steps for each node to reach ending in Z: {:?}",
             steps_to_reach_ending_in_z
         );
         //The key insight is to realize that the LCM of the vec of steps
         //will be the first time that all ghosts are in a location ending with z.
-        //Just google the LCM of the numbers:
-        //https://www.calculator.net/lcm-calculator.html
+        let steps_for_all_ghosts =
+            checked_lcm_of(steps_to_reach_ending_in_z).expect("LCM of the steps overflowed a u64");
+        println!(
+            "All ghosts reach a node ending in Z after {} steps",
+            steps_for_all_ghosts
+        );
+        steps_for_all_ghosts.to_string()
     }
 }
 
@@ -3,77 +3,215 @@ use crate::{read_input_file, SolveAdvent};
 pub struct Day9;
 
 impl SolveAdvent for Day9 {
-    fn solve_part1(path_to_file: &str) {
+    fn solve_part1(path_to_file: &str) -> String {
+        //! Predict the value after the last value of each history.
         let file_as_str = read_input_file(path_to_file);
-        let mut adder_total = 0;
-        for line in file_as_str.lines() {
-            let line_split = line
-                .split(' ')
-                .map(|item| item.trim().parse::<i32>().unwrap())
-                .collect::<Vec<_>>();
-            adder_total += extrapolate_history_part1(line_split);
-        }
+        let adder_total = sum_extrapolations(&file_as_str, |history_len| history_len as i128)
+            .unwrap_or_else(|err| panic!("{}", err));
         println!("Final Adder total: {}", adder_total);
+        adder_total.to_string()
     }
 
-    fn solve_part2(path_to_file: &str) {
+    fn solve_part2(path_to_file: &str) -> String {
+        //! Predict the value before the first value of each history.
         let file_as_str = read_input_file(path_to_file);
-        let mut adder_total = 0;
-        for line in file_as_str.lines() {
-            let line_split = line
-                .split(' ')
-                .map(|item| item.trim().parse::<i32>().unwrap())
-                .collect::<Vec<_>>();
-            adder_total += extrapolate_history_part2(line_split);
-        }
+        let adder_total =
+            sum_extrapolations(&file_as_str, |_| -1).unwrap_or_else(|err| panic!("{}", err));
         println!("Final Adder total: {}", adder_total);
+        adder_total.to_string()
     }
 }
 
-fn build_history_pyramid(history: Vec<i32>) -> Vec<Vec<i32>> {
-    //! Build the history pyramid, where each row is the delta of the row before it.
-    //! Stop when you get a row of all zeros.
-    let mut history_pyramid = vec![history];
-    while !history_pyramid[history_pyramid.len() - 1]
+pub fn report_offset(path_to_file: &str, offset: i128) -> Result<String, String> {
+    //! Predict the value of every history at the offset, where the first value of
+    //! each history is at offset 0, and report the sum along with the highest polynomial degree.
+    let file_as_str = read_input_file(path_to_file);
+    let extrapolators = parse_extrapolators(&file_as_str)?;
+    let highest_degree = extrapolators
         .iter()
-        .all(|item| item == &0)
-    {
-        let last_history = history_pyramid.last().unwrap();
-        //Build a new vector out of the delta of each item in the pyramid row above.
-        let mut new_pyramid = Vec::new();
-        for i in 0..last_history.len() - 1 {
-            new_pyramid.push(last_history[i + 1] - last_history[i]);
-        }
-        history_pyramid.push(new_pyramid);
+        .filter_map(|extrapolator| extrapolator.degree())
+        .max();
+    match highest_degree {
+        Some(degree) => println!("Highest polynomial degree: {}", degree),
+        None => println!("Every history is all zeros"),
+    }
+    let mut adder_total = 0_i128;
+    for extrapolator in extrapolators.iter() {
+        adder_total = adder_total
+            .checked_add(extrapolator.value_at(offset)?)
+            .ok_or_else(|| format!("The total at offset {} overflows", offset))?;
     }
-    history_pyramid
+    println!("Total of the values at offset {}: {}", offset, adder_total);
+    Ok(adder_total.to_string())
 }
 
-fn extrapolate_history_part1(history: Vec<i32>) -> i32 {
-    //Step1: build the pyramid shown in the advent calendar example, stop
-    //building the pyramid when all of the items are 0.
-    let mut history_pyramid = build_history_pyramid(history);
-
-    let mut adder = 0;
-    //Remove the bottom row, because we know its all zeros anyway.
-    history_pyramid.pop().unwrap();
-    while let Some(last_history) = history_pyramid.pop() {
-        //Set the new adder to the last value in the bottom row of the pyramid plus the old adder
-        adder += last_history.iter().last().unwrap();
+fn parse_extrapolators(file_as_str: &str) -> Result<Vec<Extrapolator>, String> {
+    //! Build an `Extrapolator` for each history, one per line.
+    file_as_str
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let history = line
+                .split(' ')
+                .filter(|item| !item.is_empty())
+                .map(|item| {
+                    item.trim()
+                        .parse::<i128>()
+                        .map_err(|_| format!("Invalid history value `{}`", item))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Extrapolator::new(&history)
+        })
+        .collect::<Result<Vec<_>, _>>()
+}
+
+fn sum_extrapolations(
+    file_as_str: &str,
+    offset_for: impl Fn(usize) -> i128,
+) -> Result<i128, String> {
+    //! Sum the prediction for every history, at the offset chosen using the length of the history.
+    let mut adder_total = 0_i128;
+    for extrapolator in parse_extrapolators(file_as_str)? {
+        let offset = offset_for(extrapolator.history_len);
+        adder_total = adder_total
+            .checked_add(extrapolator.value_at(offset)?)
+            .ok_or_else(|| "The total overflows".to_owned())?;
+    }
+    Ok(adder_total)
+}
+
+///The polynomial that produces a history, stored as the first value of each row of
+/// the history pyramid. With these, the value at any offset can be found using
+/// Newton's forward difference formula, without building any more of the pyramid.
+#[derive(Debug, Clone, PartialEq)]
+struct Extrapolator {
+    ///The first value of each row of the pyramid, stopping before the row of all zeros.
+    leading_differences: Vec<i128>,
+    history_len: usize,
+}
+
+impl Extrapolator {
+    fn new(history: &[i128]) -> Result<Extrapolator, String> {
+        //! Build the history pyramid, where each row is the delta of the row before it,
+        //! until a row of all zeros is found. If the rows run out first, there are too few
+        //! values to know which polynomial produced the history.
+        let mut leading_differences = Vec::new();
+        let mut row = history.to_vec();
+        while !row.iter().all(|item| item == &0) {
+            leading_differences.push(row[0]);
+            if row.len() == 1 {
+                return Err(format!(
+                    "The history {:?} never reaches a row of all zeros",
+                    history
+                ));
+            }
+            row = row
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(pair[0]))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| format!("The differences of {:?} overflow", history))?;
+        }
+        if row.is_empty() {
+            return Err("The history is empty".to_owned());
+        }
+        Ok(Extrapolator {
+            leading_differences,
+            history_len: history.len(),
+        })
+    }
+
+    fn degree(&self) -> Option<usize> {
+        //! The degree of the polynomial, which is None for a history of all zeros.
+        self.leading_differences.len().checked_sub(1)
+    }
+
+    fn value_at(&self, offset: i128) -> Result<i128, String> {
+        //! The value at the offset, where the first value of the history is at offset 0.
+        //! This is the sum of each leading difference `d_k` multiplied by `offset choose k`,
+        //! which also holds for negative offsets.
+        let overflow = || format!("The value at offset {} overflows", offset);
+        let mut value = 0_i128;
+        //offset choose 0 is 1.
+        let mut binomial = 1_i128;
+        for (k, leading_difference) in self.leading_differences.iter().enumerate() {
+            if k > 0 {
+                //offset choose k = (offset choose k-1) * (offset - k + 1) / k, which always divides exactly.
+                binomial = offset
+                    .checked_sub(k as i128 - 1)
+                    .and_then(|factor| binomial.checked_mul(factor))
+                    .ok_or_else(overflow)?
+                    / k as i128;
+            }
+            value = leading_difference
+                .checked_mul(binomial)
+                .and_then(|term| value.checked_add(term))
+                .ok_or_else(overflow)?;
+        }
+        Ok(value)
     }
-    //When the loop completes, the adder value is now the extrapolated history.
-    adder
 }
 
-fn extrapolate_history_part2(history: Vec<i32>) -> i32 {
-    //Essentially the exact same logic as part1, with small adjustments for getting a backwards history
-    //this time.
-    let mut history_pyramid = build_history_pyramid(history);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn test_example() {
+        assert_eq!(
+            sum_extrapolations(EXAMPLE_INPUT, |history_len| history_len as i128),
+            Ok(114)
+        );
+        assert_eq!(sum_extrapolations(EXAMPLE_INPUT, |_| -1), Ok(2));
+        let degrees = parse_extrapolators(EXAMPLE_INPUT)
+            .unwrap()
+            .iter()
+            .map(|extrapolator| extrapolator.degree())
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, vec![Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    fn test_far_offsets() {
+        //The triangular numbers, (t+1)(t+2)/2.
+        let extrapolator = Extrapolator::new(&[1, 3, 6, 10, 15, 21]).unwrap();
+        for t in [-1000_i128, -3, -2, -1, 0, 7, 1_000_000] {
+            assert_eq!(extrapolator.value_at(t), Ok((t + 1) * (t + 2) / 2));
+        }
+        //A cubic, t^3 - 2t.
+        let history = (0..6_i128).map(|t| t * t * t - 2 * t).collect::<Vec<_>>();
+        let extrapolator = Extrapolator::new(&history).unwrap();
+        assert_eq!(extrapolator.degree(), Some(3));
+        for t in [-1000_i128, -1, 10, 1_000_000] {
+            assert_eq!(extrapolator.value_at(t), Ok(t * t * t - 2 * t));
+        }
+        assert!(extrapolator.value_at(i128::MAX / 2).is_err());
+        assert!(extrapolator.value_at(i128::MIN).is_err());
+        assert!(extrapolator.value_at(i128::MIN + 1).is_err());
+        //A line with a slope of 0 never grows, even at the far negative offsets.
+        let extrapolator = Extrapolator::new(&[4, 4, 4]).unwrap();
+        assert_eq!(extrapolator.value_at(i128::MIN), Ok(4));
+    }
+
+    #[test]
+    fn test_constant_and_zero_histories() {
+        let extrapolator = Extrapolator::new(&[0, 0, 0]).unwrap();
+        assert_eq!(extrapolator.degree(), None);
+        assert_eq!(extrapolator.value_at(-50), Ok(0));
+        let extrapolator = Extrapolator::new(&[4, 4]).unwrap();
+        assert_eq!(extrapolator.degree(), Some(0));
+        assert_eq!(extrapolator.value_at(100), Ok(4));
+    }
 
-    let mut subtractor = 0;
-    history_pyramid.pop().unwrap();
-    while let Some(last_history) = history_pyramid.pop() {
-        subtractor = last_history.first().unwrap() - subtractor;
+    #[test]
+    fn test_no_zero_row() {
+        //The differences are 1 2 4, then 1 2, then 1, and the rows run out.
+        assert!(Extrapolator::new(&[1, 2, 4, 8]).is_err());
+        assert!(Extrapolator::new(&[5]).is_err());
+        assert!(Extrapolator::new(&[]).is_err());
+        assert!(parse_extrapolators("1 2 3\n1 2 4").is_err());
     }
-    subtractor
 }
@@ -1,8 +1,15 @@
+use cli::{Command, Flags};
+use std::env;
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::path::Path;
+use std::process;
+use std::sync::OnceLock;
+use submit::{submit_answer, GuessHistory, SubmitClient};
 
+mod cli;
 mod day1;
 mod day10;
 mod day11;
@@ -22,12 +29,29 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod math;
+mod scaffold;
+mod submit;
 
 pub fn read_input_file<P>(fp: P) -> String
 where
     P: AsRef<Path>,
 {
     //! Given a file path, returns the entire file contents as a String.
+    //! A path of `-` reads standard input instead. It is only read once, so every
+    //! part of a day sees the same input.
+    if fp.as_ref() == Path::new("-") {
+        static STDIN_INPUT: OnceLock<String> = OnceLock::new();
+        return STDIN_INPUT
+            .get_or_init(|| {
+                let mut buffer_string = String::new();
+                io::stdin()
+                    .read_to_string(&mut buffer_string)
+                    .expect("Could not read standard input to string");
+                buffer_string
+            })
+            .clone();
+    }
     let file = File::open(fp).unwrap();
     let mut reader = BufReader::new(file);
     let mut buffer_string = String::new();
@@ -39,16 +63,239 @@ where
 
 ///trait representing how to solve the days challenge for the advent calendar.
 ///Obviously, part1 is for part1 and part2 is for part2.
-/// The trait methods do not return anything, the answer to the problem
-/// should simply be printed.
+/// The trait methods print their working, and return the answer
+/// as a String so that it can be submitted. A part that has not been solved
+/// returns an empty String.
 trait SolveAdvent {
     ///How to solve part1 of the days puzzle.
-    fn solve_part1(path_to_file: &str);
+    fn solve_part1(path_to_file: &str) -> String;
     ///How to solve part2 of the days puzzle.
-    fn solve_part2(path_to_file: &str);
+    fn solve_part2(path_to_file: &str) -> String;
+}
+
+fn solve_part<T: SolveAdvent>(part: usize, path_to_file: &str) -> String {
+    //! Solve the given part of the days puzzle.
+    if part == 1 {
+        T::solve_part1(path_to_file)
+    } else {
+        T::solve_part2(path_to_file)
+    }
+}
+
+fn solve_day(day: usize, part: usize, path_to_file: &str) -> Option<String> {
+    //! The registry of every solved day. Returns None if the day has no solution.
+    let answer = match day {
+        1 => solve_part::<day1::Day1>(part, path_to_file),
+        2 => solve_part::<day2::Day2>(part, path_to_file),
+        4 => solve_part::<day4::Day4>(part, path_to_file),
+        5 => solve_part::<day5::Day5>(part, path_to_file),
+        6 => solve_part::<day6::Day6>(part, path_to_file),
+        7 => solve_part::<day7::Day7>(part, path_to_file),
+        8 => solve_part::<day8::Day8>(part, path_to_file),
+        9 => solve_part::<day9::Day9>(part, path_to_file),
+        10 => solve_part::<day10::Day10>(part, path_to_file),
+        11 => solve_part::<day11::Day11>(part, path_to_file),
+        15 => solve_part::<day15::Day15>(part, path_to_file),
+        16 => solve_part::<day16::Day16>(part, path_to_file),
+        17 => solve_part::<day17::Day17>(part, path_to_file),
+        19 => solve_part::<day19::Day19>(part, path_to_file),
+        20 => solve_part::<day20::Day20>(part, path_to_file),
+        21 => solve_part::<day21::Day21>(part, path_to_file),
+        22 => solve_part::<day22::Day22>(part, path_to_file),
+        24 => solve_part::<day24::Day24>(part, path_to_file),
+        25 => solve_part::<day25::Day25>(part, path_to_file),
+        _ => return None,
+    };
+    Some(answer)
+}
+
+fn explain_day(day: usize, part: usize, path_to_file: &str) -> Result<(), String> {
+    //! Print how the answer was reached, for the days that support it.
+    match day {
+        1 => day1::explain(path_to_file, part),
+        5 => day5::explain(path_to_file, part)?,
+        _ => return Err(format!("Day {} has no explain mode", day)),
+    }
+    Ok(())
+}
+
+fn run_day_options(day: usize, path_to_file: &str, flags: &Flags) -> Result<(), String> {
+    //! Run the extra modes that some days support, which are switched on by flags.
+    if let Some(words_path) = flags.parse_value::<String>("words")? {
+        match day {
+            1 => day1::solve_with_words(path_to_file, &words_path)?,
+            _ => return Err(format!("Day {} does not support custom digit words", day)),
+        };
+    }
+    let bag = flags.parse_value::<String>("bag")?;
+    let bag_path = flags.parse_value::<String>("bag-file")?;
+    for (bag, from_file) in [(bag, false), (bag_path, true)] {
+        let Some(bag) = bag else {
+            continue;
+        };
+        match day {
+            2 => day2::report_bag(path_to_file, &bag, from_file)?,
+            _ => return Err(format!("Day {} does not support bags of cubes", day)),
+        };
+    }
+    if let Some(rules_path) = flags.parse_value::<String>("rules")? {
+        match day {
+            7 => day7::solve_with_rules(path_to_file, &rules_path)?,
+            _ => return Err(format!("Day {} does not support custom rules", day)),
+        };
+    }
+    if flags.is_set("render") {
+        match day {
+            10 => day10::render(path_to_file, flags.is_set("color")),
+            16 => day16::render(
+                path_to_file,
+                flags.value("start"),
+                flags.is_set("energized"),
+            )?,
+            22 => day22::render(path_to_file, flags.is_set("color")),
+            _ => return Err(format!("Day {} does not support --render", day)),
+        };
+    }
+    if let Some(obj_path) = flags.parse_value::<String>("export-obj")? {
+        match day {
+            22 => day22::export_obj(path_to_file, &obj_path, flags.is_set("color"))?,
+            _ => return Err(format!("Day {} does not support --export-obj", day)),
+        };
+    }
+    if flags.is_set("query") {
+        match day {
+            22 => day22::query_disintegrations(path_to_file)?,
+            _ => return Err(format!("Day {} does not support --query", day)),
+        };
+    }
+    if flags.is_set("heatmap") {
+        match day {
+            21 => day21::heatmap(
+                path_to_file,
+                flags.value("start"),
+                flags.parse_value::<usize>("steps")?,
+                flags.value("ppm"),
+            )?,
+            _ => return Err(format!("Day {} does not support --heatmap", day)),
+        };
+    }
+    if flags.is_set("encounters") {
+        match day {
+            24 => day24::report_encounters(path_to_file),
+            _ => return Err(format!("Day {} does not support --encounters", day)),
+        };
+    }
+    let group_count = flags.parse_value::<usize>("groups")?;
+    if let Some(group_count) = group_count {
+        match day {
+            25 => day25::report_partition(path_to_file, group_count)?,
+            _ => return Err(format!("Day {} does not support --groups", day)),
+        };
+    }
+    let dot_path = flags.parse_value::<String>("export-dot")?;
+    let graphml_path = flags.parse_value::<String>("export-graphml")?;
+    for (graph_path, graphml) in [(dot_path, false), (graphml_path, true)] {
+        let Some(graph_path) = graph_path else {
+            continue;
+        };
+        match day {
+            25 => {
+                day25::export_graph(path_to_file, &graph_path, graphml, group_count.unwrap_or(2))?
+            }
+            _ => return Err(format!("Day {} does not support graph exports", day)),
+        };
+    }
+    if flags.is_set("trace") {
+        match day {
+            15 => day15::trace(path_to_file),
+            _ => return Err(format!("Day {} does not support --trace", day)),
+        };
+    }
+    let scale_factors = flags.parse_list::<usize>("scale-factors")?;
+    let between = flags.parse_list::<usize>("between")?;
+    if scale_factors.is_some() || between.is_some() {
+        match day {
+            11 => day11::report_distances(
+                path_to_file,
+                &scale_factors.unwrap_or_else(|| vec![2]),
+                between.as_deref(),
+            )?,
+            _ => return Err(format!("Day {} does not support galaxy distances", day)),
+        };
+    }
+    if let Some(offset) = flags.parse_value::<i128>("offset")? {
+        match day {
+            9 => day9::report_offset(path_to_file, offset)?,
+            _ => return Err(format!("Day {} does not support --offset", day)),
+        };
+    }
+    if let Some(presses) = flags.parse_value::<u64>("presses")? {
+        match day {
+            20 => day20::report_presses(path_to_file, presses)?,
+            _ => return Err(format!("Day {} does not support --presses", day)),
+        };
+    }
+    Ok(())
+}
+
+fn run_command(command: Command) -> Result<(), String> {
+    //! Carry out the subcommand passed on the command line.
+    match command {
+        Command::Run {
+            day,
+            part,
+            input,
+            flags,
+        } => {
+            let parts = match part {
+                Some(part) => vec![part],
+                None => vec![1, 2],
+            };
+            for part in parts {
+                solve_day(day, part, &input)
+                    .ok_or_else(|| format!("There is no solution for day {}", day))?;
+                if flags.is_set("explain") {
+                    explain_day(day, part, &input)?;
+                }
+            }
+            run_day_options(day, &input, &flags)?;
+        }
+        Command::Submit {
+            day,
+            part,
+            input,
+            endpoint,
+            history,
+        } => {
+            let answer = solve_day(day, part, &input)
+                .ok_or_else(|| format!("There is no solution for day {}", day))?;
+            let endpoint = endpoint
+                .or_else(|| env::var("ADVENT_SUBMIT_URL").ok())
+                .unwrap_or_else(|| submit::DEFAULT_ENDPOINT.to_owned());
+            let client = SubmitClient::new(&endpoint, env::var("ADVENT_SESSION").ok());
+            let mut history = GuessHistory::load(history);
+            let verdict = submit_answer(&client, &mut history, day, part, &answer)?;
+            println!(
+                "Submitted {} for day {} part {}: {}",
+                answer, day, part, verdict
+            );
+        }
+        Command::New { day } => {
+            let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+            scaffold::scaffold_day(&src_dir, day)?;
+        }
+    }
+    Ok(())
 }
 
 fn main() {
-    day22::Day22::solve_part1("input.txt");
-    day22::Day22::solve_part2("input.txt");
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let command = Command::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::USAGE);
+        process::exit(1);
+    });
+    if let Err(err) = run_command(command) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,256 @@
+//! Exact integer math shared between the days.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+///Greatest common divisor and least common multiple for the integer
+/// types the puzzles need.
+pub trait Gcd: Sized {
+    ///The greatest common divisor, which is never negative, or None if it overflows.
+    fn checked_gcd(self, other: Self) -> Option<Self>;
+    ///The least common multiple, which is never negative, or None if it overflows.
+    fn checked_lcm(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned_gcd {
+    ($int:ty) => {
+        impl Gcd for $int {
+            fn checked_gcd(self, other: Self) -> Option<Self> {
+                //! Euclid's algorithm, which can never overflow for unsigned integers.
+                let (mut a, mut b) = (self, other);
+                while b != 0 {
+                    (a, b) = (b, a % b);
+                }
+                Some(a)
+            }
+
+            fn checked_lcm(self, other: Self) -> Option<Self> {
+                if self == 0 || other == 0 {
+                    return Some(0);
+                }
+                (self / self.checked_gcd(other)?).checked_mul(other)
+            }
+        }
+    };
+}
+
+impl_unsigned_gcd!(u64);
+impl_unsigned_gcd!(u128);
+
+impl Gcd for i128 {
+    fn checked_gcd(self, other: Self) -> Option<Self> {
+        //! Found from the absolute values, which do not fit in an i128 for `i128::MIN`,
+        //! so `i128::MIN.checked_gcd(0)` is None.
+        let gcd = self.unsigned_abs().checked_gcd(other.unsigned_abs())?;
+        i128::try_from(gcd).ok()
+    }
+
+    fn checked_lcm(self, other: Self) -> Option<Self> {
+        let lcm = self.unsigned_abs().checked_lcm(other.unsigned_abs())?;
+        i128::try_from(lcm).ok()
+    }
+}
+
+pub fn checked_lcm_of<T, I>(numbers: I) -> Option<T>
+where
+    T: Gcd + From<u8>,
+    I: IntoIterator<Item = T>,
+{
+    //! The least common multiple of every number, or None if it overflows.
+    numbers
+        .into_iter()
+        .try_fold(T::from(1), |lcm, number| lcm.checked_lcm(number))
+}
+
+pub fn isqrt(n: u128) -> u128 {
+    //! The exact integer square root, which is the largest `r` such that `r*r <= n`.
+    if n < 2 {
+        return n;
+    }
+    //Start from the floating point estimate, and correct it using exact arithmetic.
+    let mut root = (n as f64).sqrt() as u128;
+    while root.checked_mul(root).is_none_or(|square| square > n) {
+        root -= 1;
+    }
+    while (root + 1)
+        .checked_mul(root + 1)
+        .is_some_and(|square| square <= n)
+    {
+        root += 1;
+    }
+    root
+}
+
+///An exact fraction, always stored in lowest terms with a positive denominator.
+/// All of the arithmetic is checked, returning None on overflow or division by zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Option<Rational> {
+        //! Build the fraction `numerator/denominator`, which fails if the denominator is 0.
+        //! The fraction is reduced using the absolute values, so `i128::MIN` can be divided down
+        //! into range, and fails if the reduced fraction does not fit.
+        if denominator == 0 {
+            return None;
+        }
+        let (numerator_abs, denominator_abs) =
+            (numerator.unsigned_abs(), denominator.unsigned_abs());
+        let divisor = numerator_abs.checked_gcd(denominator_abs)?;
+        let (numerator_abs, denominator_abs) = (numerator_abs / divisor, denominator_abs / divisor);
+        let numerator = if (numerator < 0) != (denominator < 0) {
+            0_i128.checked_sub_unsigned(numerator_abs)?
+        } else {
+            i128::try_from(numerator_abs).ok()?
+        };
+        Some(Rational {
+            numerator,
+            denominator: i128::try_from(denominator_abs).ok()?,
+        })
+    }
+
+    pub fn from_integer(integer: i128) -> Rational {
+        Rational {
+            numerator: integer,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        let denominator = self.denominator.checked_lcm(other.denominator)?;
+        let left = self.numerator.checked_mul(denominator / self.denominator)?;
+        let right = other
+            .numerator
+            .checked_mul(denominator / other.denominator)?;
+        Rational::new(left.checked_add(right)?, denominator)
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        //! Cross cancel before multiplying to keep the intermediate values small.
+        let divisor1 = self.numerator.checked_gcd(other.denominator)?.max(1);
+        let divisor2 = other.numerator.checked_gcd(self.denominator)?.max(1);
+        let numerator = (self.numerator / divisor1).checked_mul(other.numerator / divisor2)?;
+        let denominator =
+            (self.denominator / divisor2).checked_mul(other.denominator / divisor1)?;
+        Rational::new(numerator, denominator)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        //! Compare using continued fractions, which unlike cross multiplication
+        //! can never overflow.
+        fn compare(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+            //Compares a/b to c/d, where b and d are positive.
+            let (quotient1, remainder1) = (a.div_euclid(b), a.rem_euclid(b));
+            let (quotient2, remainder2) = (c.div_euclid(d), c.rem_euclid(d));
+            if quotient1 != quotient2 {
+                return quotient1.cmp(&quotient2);
+            }
+            match (remainder1 == 0, remainder2 == 0) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                //The fractional parts compare in the opposite order to their reciprocals.
+                (false, false) => compare(d, remainder2, b, remainder1),
+            }
+        }
+        compare(
+            self.numerator,
+            self.denominator,
+            other.numerator,
+            other.denominator,
+        )
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            return write!(f, "{}", self.numerator);
+        }
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(12_u64.checked_gcd(18), Some(6));
+        assert_eq!((-12_i128).checked_gcd(18), Some(6));
+        assert_eq!(0_u128.checked_gcd(7), Some(7));
+        assert_eq!(4_u64.checked_lcm(6), Some(12));
+        assert_eq!((-4_i128).checked_lcm(6), Some(12));
+        assert_eq!(u64::MAX.checked_lcm(2), None);
+        //The gcd of i128::MIN and 0 is 2^127, which is one more than i128::MAX.
+        assert_eq!(i128::MIN.checked_gcd(0), None);
+        assert_eq!(i128::MIN.checked_gcd(i128::MIN), None);
+        assert_eq!(i128::MIN.checked_gcd(6), Some(2));
+        assert_eq!(i128::MIN.checked_lcm(1), None);
+        assert_eq!(i128::MAX.checked_lcm(-1), Some(i128::MAX));
+        assert_eq!(checked_lcm_of([2_u64, 3, 4, 5]), Some(60));
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..10_000_u128 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n);
+        }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1 << 100) - 1), (1 << 50) - 1);
+    }
+
+    #[test]
+    fn test_rational() {
+        let half = Rational::new(2, 4).unwrap();
+        let third = Rational::new(-1, -3).unwrap();
+        assert_eq!((half.numerator(), half.denominator()), (1, 2));
+        assert_eq!(half.checked_add(third), Rational::new(5, 6));
+        assert_eq!(half.checked_mul(third), Rational::new(1, 6));
+        assert_eq!(Rational::new(1, 0), None);
+        assert_eq!(
+            Rational::new(i128::MIN, i128::MIN),
+            Some(Rational::from_integer(1))
+        );
+        assert_eq!(
+            Rational::new(i128::MIN, 2),
+            Some(Rational::from_integer(i128::MIN / 2))
+        );
+        assert_eq!(
+            Rational::new(i128::MIN, 1),
+            Some(Rational::from_integer(i128::MIN))
+        );
+        assert_eq!(Rational::new(i128::MIN, -1), None);
+        assert_eq!(Rational::new(1, i128::MIN), None);
+        assert_eq!(Rational::new(2, i128::MIN), Rational::new(-1, 1 << 126));
+        assert!(third < half);
+        assert!(Rational::new(-7, 2).unwrap() < Rational::from_integer(-3));
+        assert!(Rational::new(i128::MAX, 3).unwrap() > Rational::new(i128::MAX - 1, 3).unwrap());
+        assert_eq!(Rational::new(6, -4).unwrap().to_string(), "-3/2");
+    }
+}
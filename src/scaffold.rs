@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::Path;
+
+///The module generated for a new day. Every `{N}` is replaced by the day number.
+const DAY_TEMPLATE: &str = r#"use crate::{read_input_file, SolveAdvent};
+
+pub struct Day{N};
+
+impl SolveAdvent for Day{N} {
+    fn solve_part1(path_to_file: &str) -> String {
+        let file_as_str = read_input_file(path_to_file);
+        let parsed_input = parse_input(&file_as_str);
+        let answer = compute_part1(&parsed_input);
+        println!("Part1 answer: {}", answer);
+        answer.to_string()
+    }
+
+    fn solve_part2(path_to_file: &str) -> String {
+        let file_as_str = read_input_file(path_to_file);
+        let parsed_input = parse_input(&file_as_str);
+        let answer = compute_part2(&parsed_input);
+        println!("Part2 answer: {}", answer);
+        answer.to_string()
+    }
+}
+
+fn parse_input(file_as_str: &str) -> Vec<String> {
+    //! Process the input file into something easier to work with.
+    file_as_str
+        .lines()
+        .map(|line| line.trim().to_owned())
+        .collect::<Vec<_>>()
+}
+
+fn compute_part1(parsed_input: &[String]) -> usize {
+    todo!("Solve part1 using the {} input lines", parsed_input.len())
+}
+
+fn compute_part2(parsed_input: &[String]) -> usize {
+    todo!("Solve part2 using the {} input lines", parsed_input.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///The example given in the puzzle description.
+    const EXAMPLE_INPUT: &str = "";
+
+    #[test]
+    fn test_part1_example() {
+        let parsed_input = parse_input(EXAMPLE_INPUT);
+        assert_eq!(compute_part1(&parsed_input), 0);
+    }
+
+    #[test]
+    fn test_part2_example() {
+        let parsed_input = parse_input(EXAMPLE_INPUT);
+        assert_eq!(compute_part2(&parsed_input), 0);
+    }
+}
+"#;
+
+pub fn scaffold_day(src_dir: &Path, day: usize) -> Result<(), String> {
+    //! Generate `dayN.rs` in the `src_dir`, and register it in `main.rs`.
+    //! Refuses to touch anything if the day already exists.
+    let day_path = src_dir.join(format!("day{}.rs", day));
+    if day_path.exists() {
+        return Err(format!("{} already exists", day_path.display()));
+    }
+    let main_path = src_dir.join("main.rs");
+    let main_rs = fs::read_to_string(&main_path)
+        .map_err(|err| format!("Could not read {}: {}", main_path.display(), err))?;
+    let registered_main_rs = register_day(&main_rs, day)?;
+
+    fs::write(&day_path, DAY_TEMPLATE.replace("{N}", &day.to_string()))
+        .map_err(|err| format!("Could not write {}: {}", day_path.display(), err))?;
+    fs::write(&main_path, registered_main_rs)
+        .map_err(|err| format!("Could not write {}: {}", main_path.display(), err))?;
+    println!("Created {} and registered Day{}", day_path.display(), day);
+    Ok(())
+}
+
+fn register_day(main_rs: &str, day: usize) -> Result<String, String> {
+    //! Add the `mod dayN;` declaration and the `solve_day` registry entry to
+    //! the contents of `main.rs`, keeping both in sorted order.
+    let mod_declaration = format!("mod day{};", day);
+    let registry_entry = format!(
+        "        {} => solve_part::<day{}::Day{}>(part, path_to_file),",
+        day, day, day
+    );
+    let mut lines = main_rs
+        .lines()
+        .map(|line| line.to_owned())
+        .collect::<Vec<_>>();
+    if lines.contains(&mod_declaration) {
+        return Err(format!("Day{} is already declared in main.rs", day));
+    }
+
+    //The mod declarations are one contiguous block, sorted the way rustfmt sorts them.
+    let first_mod = lines
+        .iter()
+        .position(|line| line.starts_with("mod "))
+        .ok_or_else(|| "Could not find the mod declarations in main.rs".to_owned())?;
+    let mod_count = lines[first_mod..]
+        .iter()
+        .take_while(|line| line.starts_with("mod "))
+        .count();
+    let mut mod_block = lines
+        .splice(first_mod..first_mod + mod_count, [])
+        .collect::<Vec<_>>();
+    mod_block.push(mod_declaration);
+    mod_block.sort_by_key(|line| line.trim_end_matches(';').to_owned());
+    lines.splice(first_mod..first_mod, mod_block);
+
+    //The registry entry goes before the first day with a larger number, or
+    //before the catch all arm.
+    let registry_start = lines
+        .iter()
+        .position(|line| line.starts_with("fn solve_day("))
+        .ok_or_else(|| "Could not find solve_day in main.rs".to_owned())?;
+    let insert_at = lines[registry_start..]
+        .iter()
+        .position(|line| {
+            let arm = line.trim_start();
+            if arm.starts_with("_ =>") {
+                return true;
+            }
+            match arm.split(" =>").next().unwrap().parse::<usize>() {
+                Ok(registered_day) => registered_day > day,
+                Err(_) => false,
+            }
+        })
+        .ok_or_else(|| "Could not find the end of the solve_day registry".to_owned())?;
+    lines.insert(registry_start + insert_at, registry_entry);
+
+    let mut registered_main_rs = lines.join("\n");
+    registered_main_rs.push('\n');
+    Ok(registered_main_rs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAIN_RS: &str = "mod cli;
+mod day1;
+mod day10;
+mod day2;
+mod submit;
+
+fn solve_day(day: usize, part: usize, path_to_file: &str) -> Option<String> {
+    let answer = match day {
+        1 => solve_part::<day1::Day1>(part, path_to_file),
+        2 => solve_part::<day2::Day2>(part, path_to_file),
+        10 => solve_part::<day10::Day10>(part, path_to_file),
+        _ => return None,
+    };
+    Some(answer)
+}
+";
+
+    #[test]
+    fn test_register_day_in_order() {
+        let registered = register_day(MAIN_RS, 3).unwrap();
+        assert!(registered.contains("mod day2;\nmod day3;\nmod submit;"));
+        assert!(registered.contains(
+            "(part, path_to_file),\n        3 => solve_part::<day3::Day3>(part, path_to_file),\n        10 =>"
+        ));
+    }
+
+    #[test]
+    fn test_register_day_at_end() {
+        let registered = register_day(MAIN_RS, 25).unwrap();
+        assert!(registered.contains("mod day2;\nmod day25;\nmod submit;"));
+        assert!(registered.contains(
+            "        25 => solve_part::<day25::Day25>(part, path_to_file),\n        _ => return None,"
+        ));
+    }
+
+    #[test]
+    fn test_register_existing_day_is_refused() {
+        assert!(register_day(MAIN_RS, 10).is_err());
+    }
+
+    #[test]
+    fn test_scaffold_refuses_to_overwrite() {
+        let src_dir = std::env::temp_dir().join(format!("advent_scaffold_{}", std::process::id()));
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(src_dir.join("main.rs"), MAIN_RS).unwrap();
+        fs::write(src_dir.join("day3.rs"), "//Already solved").unwrap();
+
+        assert!(scaffold_day(&src_dir, 3).is_err());
+        assert_eq!(
+            fs::read_to_string(src_dir.join("day3.rs")).unwrap(),
+            "//Already solved"
+        );
+        assert_eq!(
+            fs::read_to_string(src_dir.join("main.rs")).unwrap(),
+            MAIN_RS
+        );
+
+        scaffold_day(&src_dir, 4).unwrap();
+        let generated = fs::read_to_string(src_dir.join("day4.rs")).unwrap();
+        assert!(generated.contains("pub struct Day4;"));
+        assert!(generated.contains("impl SolveAdvent for Day4 {"));
+        fs::remove_dir_all(&src_dir).unwrap();
+    }
+}
@@ -0,0 +1,476 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+///The endpoint answers are posted to when no other endpoint is configured.
+pub const DEFAULT_ENDPOINT: &str = "https://adventofcode.com/2023/day/{day}/answer";
+
+///What the website had to say about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    ///The answer is wrong, but the website gave no hint as to why.
+    Incorrect,
+    ///An answer was submitted too recently, so this one was not checked.
+    RateLimited(Duration),
+}
+
+impl Verdict {
+    fn from_response(response_body: &str) -> Option<Verdict> {
+        //! Parse the html body returned by the website into a `Verdict`.
+        //! The order of the checks matters, as a too high/too low answer
+        //! also contains the text of an incorrect answer.
+        if response_body.contains("That's the right answer") {
+            Some(Verdict::Correct)
+        } else if response_body.contains("answer is too high") {
+            Some(Verdict::TooHigh)
+        } else if response_body.contains("answer is too low") {
+            Some(Verdict::TooLow)
+        } else if response_body.contains("That's not the right answer") {
+            Some(Verdict::Incorrect)
+        } else if response_body.contains("You gave an answer too recently") {
+            Some(Verdict::RateLimited(parse_wait_time(response_body)))
+        } else {
+            None
+        }
+    }
+
+    fn is_wrong(&self) -> bool {
+        //! Whether the answer is known to be wrong. A rate limited answer was never
+        //! checked, so it is not known to be wrong.
+        matches!(
+            self,
+            Verdict::TooHigh | Verdict::TooLow | Verdict::Incorrect
+        )
+    }
+
+    fn history_repr(&self) -> String {
+        //! The representation of the verdict stored in the history file.
+        match self {
+            Verdict::Correct => "correct".to_owned(),
+            Verdict::TooHigh => "too-high".to_owned(),
+            Verdict::TooLow => "too-low".to_owned(),
+            Verdict::Incorrect => "incorrect".to_owned(),
+            Verdict::RateLimited(wait) => format!("rate-limited:{}", wait.as_secs()),
+        }
+    }
+
+    fn from_history_repr(repr: &str) -> Option<Verdict> {
+        //! The inverse of `history_repr`.
+        match repr {
+            "correct" => Some(Verdict::Correct),
+            "too-high" => Some(Verdict::TooHigh),
+            "too-low" => Some(Verdict::TooLow),
+            "incorrect" => Some(Verdict::Incorrect),
+            other => {
+                let seconds = other.strip_prefix("rate-limited:")?.parse::<u64>().ok()?;
+                Some(Verdict::RateLimited(Duration::from_secs(seconds)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "That's the right answer!"),
+            Verdict::TooHigh => write!(f, "Wrong answer, it is too high"),
+            Verdict::TooLow => write!(f, "Wrong answer, it is too low"),
+            Verdict::Incorrect => write!(f, "Wrong answer"),
+            Verdict::RateLimited(wait) => {
+                write!(f, "Rate limited, try again in {} seconds", wait.as_secs())
+            }
+        }
+    }
+}
+
+fn parse_wait_time(response_body: &str) -> Duration {
+    //! The website phrases the wait as `You have 1m 30s left to wait`.
+    //! Sum up each of the `h`, `m` and `s` components preceding `left to wait`.
+    let wait_text = response_body
+        .split("left to wait")
+        .next()
+        .and_then(|before| before.rsplit("You have").next())
+        .unwrap_or_default();
+    let mut seconds = 0;
+    for component in wait_text.split_whitespace() {
+        let (number, multiplier) = if let Some(hours) = component.strip_suffix('h') {
+            (hours, 3600)
+        } else if let Some(minutes) = component.strip_suffix('m') {
+            (minutes, 60)
+        } else if let Some(secs) = component.strip_suffix('s') {
+            (secs, 1)
+        } else {
+            continue;
+        };
+        if let Ok(number) = number.parse::<u64>() {
+            seconds += number * multiplier;
+        }
+    }
+    Duration::from_secs(seconds)
+}
+
+///A single answer submitted in the past, and the verdict it received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Guess {
+    pub day: usize,
+    pub part: usize,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Guess {
+    fn from_line(line: &str) -> Option<Guess> {
+        //! Each line of the history file is tab separated: `day part answer verdict`
+        let mut line_split = line.split('\t');
+        let day = line_split.next()?.parse::<usize>().ok()?;
+        let part = line_split.next()?.parse::<usize>().ok()?;
+        let answer = line_split.next()?.to_owned();
+        let verdict = Verdict::from_history_repr(line_split.next()?)?;
+        Some(Guess {
+            day,
+            part,
+            answer,
+            verdict,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.day,
+            self.part,
+            self.answer,
+            self.verdict.history_repr()
+        )
+    }
+}
+
+///Every guess ever submitted, backed by a file on disk.
+#[derive(Debug, Clone)]
+pub struct GuessHistory {
+    path: PathBuf,
+    guesses: Vec<Guess>,
+}
+
+impl GuessHistory {
+    pub fn load<P: Into<PathBuf>>(path: P) -> GuessHistory {
+        //! Load the history from the file, which may not exist yet. Lines that
+        //! can not be understood are ignored.
+        let path = path.into();
+        let guesses = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(Guess::from_line)
+            .collect::<Vec<_>>();
+        GuessHistory { path, guesses }
+    }
+
+    pub fn check(&self, day: usize, part: usize, answer: &str) -> Result<(), String> {
+        //! Refuse to submit an answer that is already known to be wrong, either because
+        //! the exact answer was already rejected, or because it is outside the bounds established
+        //! by earlier too high/too low verdicts.
+        let numeric_answer = answer.parse::<i128>().ok();
+        for guess in self
+            .guesses
+            .iter()
+            .filter(|guess| guess.day == day && guess.part == part)
+        {
+            if guess.verdict == Verdict::Correct {
+                return Err(format!(
+                    "Day {} part {} was already solved with {}",
+                    day, part, guess.answer
+                ));
+            }
+            if guess.answer == answer && guess.verdict.is_wrong() {
+                return Err(format!("{} was already submitted and was wrong", answer));
+            }
+            let (Some(numeric_answer), Ok(guessed)) =
+                (numeric_answer, guess.answer.parse::<i128>())
+            else {
+                continue;
+            };
+            if guess.verdict == Verdict::TooHigh && numeric_answer >= guessed {
+                return Err(format!("{} is known to be too high", answer));
+            }
+            if guess.verdict == Verdict::TooLow && numeric_answer <= guessed {
+                return Err(format!("{} is known to be too low", answer));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn record(&mut self, guess: Guess) -> Result<(), String> {
+        //! Append the guess to the history file.
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|err| format!("Could not open {}: {}", self.path.display(), err))?;
+        writeln!(file, "{}", guess.to_line())
+            .map_err(|err| format!("Could not write to {}: {}", self.path.display(), err))?;
+        self.guesses.push(guess);
+        Ok(())
+    }
+}
+
+///Posts answers to the website using `curl`.
+#[derive(Debug, Clone)]
+pub struct SubmitClient {
+    ///The url to post to. Any `{day}` is replaced by the day number.
+    endpoint: String,
+    ///The value of the website's session cookie.
+    session: Option<String>,
+}
+
+impl SubmitClient {
+    pub fn new(endpoint: &str, session: Option<String>) -> SubmitClient {
+        SubmitClient {
+            endpoint: endpoint.to_owned(),
+            session,
+        }
+    }
+
+    pub fn submit(&self, day: usize, part: usize, answer: &str) -> Result<Verdict, String> {
+        //! Post the answer as a form, and parse the response into a `Verdict`.
+        let url = self.endpoint.replace("{day}", &day.to_string());
+        let mut curl = Command::new("curl");
+        curl.args(["--silent", "--show-error", "--location"])
+            .args(["--data-urlencode", &format!("level={}", part)])
+            .args(["--data-urlencode", &format!("answer={}", answer)]);
+        //The session cookie is passed through a config on stdin, as anything in the arguments
+        //can be read by every user from the process list.
+        let session_config = self.session.as_deref().map(session_config).transpose()?;
+        if session_config.is_some() {
+            curl.args(["--config", "-"]).stdin(Stdio::piped());
+        } else {
+            curl.stdin(Stdio::null());
+        }
+        let mut child = curl
+            .arg(&url)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Could not run curl: {}", err))?;
+        if let (Some(session_config), Some(mut stdin)) = (session_config, child.stdin.take()) {
+            stdin
+                .write_all(session_config.as_bytes())
+                .map_err(|err| format!("Could not pass the session to curl: {}", err))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|err| format!("Could not run curl: {}", err))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Posting to {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let response_body = String::from_utf8_lossy(&output.stdout);
+        Verdict::from_response(&response_body)
+            .ok_or_else(|| format!("Could not understand the response: {}", response_body))
+    }
+}
+
+fn session_config(session: &str) -> Result<String, String> {
+    //! A curl config line setting the session cookie. Quotes and backslashes are escaped,
+    //! as the value is a quoted string, and line breaks are refused so that the session
+    //! cannot add lines of its own to the config.
+    if session.contains(['\n', '\r']) {
+        return Err("The session cookie cannot contain line breaks".to_owned());
+    }
+    let escaped_session = session.replace('\\', "\\\\").replace('"', "\\\"");
+    Ok(format!("cookie = \"session={}\"\n", escaped_session))
+}
+
+pub fn submit_answer(
+    client: &SubmitClient,
+    history: &mut GuessHistory,
+    day: usize,
+    part: usize,
+    answer: &str,
+) -> Result<Verdict, String> {
+    //! Submit the answer unless the history already knows it to be wrong,
+    //! recording the verdict in the history.
+    if answer.is_empty() {
+        return Err(format!(
+            "Day {} part {} did not return an answer",
+            day, part
+        ));
+    }
+    history.check(day, part, answer)?;
+    let verdict = client.submit(day, part, answer)?;
+    history.record(Guess {
+        day,
+        part,
+        answer: answer.to_owned(),
+        verdict,
+    })?;
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn spawn_mock_server(response_body: &'static str) -> (String, thread::JoinHandle<String>) {
+        //! Serve a single request with the given body, returning the endpoint
+        //! and a handle that yields the request body the server received.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!(
+            "http://{}/2023/day/{{day}}/answer",
+            listener.local_addr().unwrap()
+        );
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                request_line.push_str(&header);
+                if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse::<usize>().unwrap();
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response_body.len(),
+                response_body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            format!(
+                "{}{}",
+                request_line,
+                String::from_utf8(request_body).unwrap()
+            )
+        });
+        (endpoint, handle)
+    }
+
+    fn temporary_history(name: &str) -> GuessHistory {
+        let path = std::env::temp_dir().join(format!(
+            "advent_guess_history_{}_{}.txt",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        GuessHistory::load(path)
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        assert_eq!(
+            Verdict::from_response("<p>That's the right answer!  You are one gold star"),
+            Some(Verdict::Correct)
+        );
+        assert_eq!(
+            Verdict::from_response("That's not the right answer; your answer is too high."),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(
+            Verdict::from_response("That's not the right answer; your answer is too low."),
+            Some(Verdict::TooLow)
+        );
+        assert_eq!(
+            Verdict::from_response("That's not the right answer.  If you're stuck"),
+            Some(Verdict::Incorrect)
+        );
+        assert_eq!(
+            Verdict::from_response(
+                "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 30s left to wait."
+            ),
+            Some(Verdict::RateLimited(Duration::from_secs(90)))
+        );
+        assert_eq!(Verdict::from_response("<html>Something else</html>"), None);
+    }
+
+    #[test]
+    fn test_history_refuses_known_bad_answers() {
+        let mut history = temporary_history("bounds");
+        for (answer, verdict) in [("100", Verdict::TooHigh), ("20", Verdict::TooLow)] {
+            history
+                .record(Guess {
+                    day: 6,
+                    part: 1,
+                    answer: answer.to_owned(),
+                    verdict,
+                })
+                .unwrap();
+        }
+        //Reload from disk, to check the history file round trips.
+        let history = GuessHistory::load(history.path.clone());
+        assert_eq!(history.guesses.len(), 2);
+        assert!(history.check(6, 1, "100").is_err());
+        assert!(history.check(6, 1, "150").is_err());
+        assert!(history.check(6, 1, "20").is_err());
+        assert!(history.check(6, 1, "5").is_err());
+        assert!(history.check(6, 1, "50").is_ok());
+        //Guesses for other parts do not interfere.
+        assert!(history.check(6, 2, "150").is_ok());
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn test_submit_against_mock_server() {
+        let (endpoint, server) =
+            spawn_mock_server("<p>That's not the right answer; your answer is too low.</p>");
+        let client = SubmitClient::new(&endpoint, Some("abc".to_owned()));
+        let mut history = temporary_history("mock");
+        let verdict = submit_answer(&client, &mut history, 6, 2, "42").unwrap();
+        assert_eq!(verdict, Verdict::TooLow);
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2023/day/6/answer"));
+        assert!(request.contains("level=2&answer=42"));
+        assert!(request.contains("Cookie: session=abc\r\n"));
+
+        //The guess is recorded, so submitting it again is refused without contacting the server.
+        assert_eq!(history.guesses.len(), 1);
+        assert!(submit_answer(&client, &mut history, 6, 2, "42").is_err());
+        assert!(submit_answer(&client, &mut history, 6, 2, "41").is_err());
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn test_session_config() {
+        assert_eq!(
+            session_config("abc"),
+            Ok("cookie = \"session=abc\"\n".to_owned())
+        );
+        assert_eq!(
+            session_config("a\"b\\c"),
+            Ok("cookie = \"session=a\\\"b\\\\c\"\n".to_owned())
+        );
+        assert!(session_config("abc\nurl = \"http://example.com\"").is_err());
+    }
+
+    #[test]
+    fn test_rate_limited_answers_can_be_resubmitted() {
+        let (endpoint, server) =
+            spawn_mock_server("You gave an answer too recently; You have 37s left to wait.");
+        let client = SubmitClient::new(&endpoint, None);
+        let mut history = temporary_history("rate_limited");
+        let verdict = submit_answer(&client, &mut history, 1, 1, "1234").unwrap();
+        assert!(!server.join().unwrap().contains("Cookie"));
+        assert_eq!(verdict, Verdict::RateLimited(Duration::from_secs(37)));
+        assert!(history.check(1, 1, "1234").is_ok());
+        fs::remove_file(&history.path).unwrap();
+    }
+}